@@ -2,6 +2,11 @@
 
 use std::error::Error;
 
+use crate::{alphabet::Alphabet, Cipher, CipherError};
+
+/// Affine key `(a, b)` of the map `a * x + b`.
+pub type Key = (i32, i32);
+
 /// https://en.wikipedia.org/wiki/Extended_Euclidean_algorithm#Computing_multiplicative_inverses_in_modular_structures
 pub fn inverse(a: i32, n: i32) -> Option<i32> {
     let (mut t, mut new_t) = (0, 1);
@@ -24,7 +29,7 @@ pub fn inverse(a: i32, n: i32) -> Option<i32> {
     Some(t)
 }
 
-fn gcd(a: i32, b: i32) -> i32 {
+pub fn gcd(a: i32, b: i32) -> i32 {
     if b == 0 {
         return a;
     }
@@ -32,46 +37,137 @@ fn gcd(a: i32, b: i32) -> i32 {
     gcd(b, a % b)
 }
 
-pub fn encrypt(plaintext: &str, a: i32, b: i32) -> Result<String, Box<dyn Error>> {
-    if gcd(a, 26) != 1 {
-        return Err("Invalid 'a' parameter (gcd(a, 26) != 1)".into());
+pub fn encrypt(
+    plaintext: &str,
+    a: i32,
+    b: i32,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    let m = alphabet.len() as i32;
+    if gcd(a, m) != 1 {
+        return Err(CipherError::NotInvertible(format!(
+            "Invalid 'a' parameter (gcd(a, {}) != 1)",
+            m
+        ))
+        .into());
+    }
+
+    Ok(alphabet.map(plaintext, |x| a * x + b))
+}
+
+pub fn decrypt(
+    ciphertext: &str,
+    a: i32,
+    b: i32,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    let a_inv = inverse_a(a, alphabet.len() as i32)?;
+    Ok(alphabet.map(ciphertext, |y| a_inv * (y - b)))
+}
+
+/// Inverse of the 'a' parameter modulo the alphabet size `m`.
+fn inverse_a(a: i32, m: i32) -> Result<i32, Box<dyn Error>> {
+    if gcd(a, m) != 1 {
+        return Err(CipherError::NotInvertible(format!(
+            "Invalid 'a' parameter (gcd(a, {}) != 1)",
+            m
+        ))
+        .into());
     }
 
-    let ciphertext: String = plaintext
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphabetic() {
-                let base = if c.is_uppercase() { b'A' } else { b'a' };
-                let x = (c as u8 - base) as i32;
-                (((a * x + b) % 26) as u8 + base) as char
-            } else {
-                c
-            }
-        })
-        .collect();
-
-    Ok(ciphertext)
+    Ok(inverse(a, m).ok_or(CipherError::NotInvertible(
+        "Invalid 'a' parameter ('a' could not be inverted)".into(),
+    ))?)
 }
 
-pub fn decrypt(ciphertext: &str, a: i32, b: i32) -> Result<String, Box<dyn Error>> {
-    if gcd(a, 26) != 1 {
-        return Err("Invalid 'a' parameter (gcd(a, 26) != 1)".into());
+/// Keyed affine cipher: the mapped position is looked up in the alphabet
+/// mixed with `keyword`.
+pub fn encrypt_keyed(
+    plaintext: &str,
+    a: i32,
+    b: i32,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    inverse_a(a, alphabet.len() as i32)?;
+    let mixed = alphabet.keyed(keyword)?;
+    Ok(alphabet.translate(plaintext, &mixed, |x| a * x + b))
+}
+
+pub fn decrypt_keyed(
+    ciphertext: &str,
+    a: i32,
+    b: i32,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    let a_inv = inverse_a(a, alphabet.len() as i32)?;
+    let mixed = alphabet.keyed(keyword)?;
+    Ok(mixed.translate(ciphertext, alphabet, |y| a_inv * (y - b)))
+}
+
+fn check_byte_a(a: i32) -> Result<(), Box<dyn Error>> {
+    if a.rem_euclid(2) != 1 {
+        return Err(CipherError::NotInvertible(
+            "Invalid 'a' parameter (a must be odd mod 256)".into(),
+        )
+        .into());
     }
+    Ok(())
+}
+
+/// Affine map over Z_256 applied to every byte of arbitrary data.
+pub fn encrypt_bytes(data: &[u8], a: i32, b: i32) -> Result<Vec<u8>, Box<dyn Error>> {
+    check_byte_a(a)?;
+    Ok(data
+        .iter()
+        .map(|&x| (a * x as i32 + b).rem_euclid(256) as u8)
+        .collect())
+}
 
-    let a_inv = inverse(a, 26).ok_or("Invalid 'a' parameter ('a' could not be inverted)")?;
-
-    let plaintext: String = ciphertext
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphabetic() {
-                let base = if c.is_uppercase() { b'A' } else { b'a' };
-                let y = (c as u8 - base) as i32;
-                (((a_inv * (y - b + 26)) % 26) as u8 + base) as char
-            } else {
-                c
-            }
-        })
-        .collect();
-
-    Ok(plaintext)
+pub fn decrypt_bytes(data: &[u8], a: i32, b: i32) -> Result<Vec<u8>, Box<dyn Error>> {
+    check_byte_a(a)?;
+    let a_inv = inverse(a.rem_euclid(256), 256).ok_or(CipherError::NotInvertible(
+        "Invalid 'a' parameter ('a' could not be inverted)".into(),
+    ))?;
+    Ok(data
+        .iter()
+        .map(|&y| (a_inv * (y as i32 - b)).rem_euclid(256) as u8)
+        .collect())
+}
+
+/// Affine cipher keyed with `(a, b)`.
+#[derive(Debug, Clone)]
+pub struct Affine {
+    pub alphabet: Alphabet,
+}
+
+impl Cipher for Affine {
+    type Key = (i32, i32);
+
+    fn encrypt(&self, plaintext: &str, key: &(i32, i32)) -> Result<String, Box<dyn Error>> {
+        encrypt(plaintext, key.0, key.1, &self.alphabet)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &(i32, i32)) -> Result<String, Box<dyn Error>> {
+        decrypt(ciphertext, key.0, key.1, &self.alphabet)
+    }
+}
+
+/// Affine cipher over a keyword-mixed alphabet, keyed with the keyword and `(a, b)`.
+#[derive(Debug, Clone)]
+pub struct KeyedAffine {
+    pub alphabet: Alphabet,
+}
+
+impl Cipher for KeyedAffine {
+    type Key = (String, Key);
+
+    fn encrypt(&self, plaintext: &str, key: &(String, Key)) -> Result<String, Box<dyn Error>> {
+        encrypt_keyed(plaintext, key.1 .0, key.1 .1, &key.0, &self.alphabet)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &(String, Key)) -> Result<String, Box<dyn Error>> {
+        decrypt_keyed(ciphertext, key.1 .0, key.1 .1, &key.0, &self.alphabet)
+    }
 }
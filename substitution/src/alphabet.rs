@@ -0,0 +1,159 @@
+// 2024 Paweł Rybak
+
+use std::{collections::HashMap, error::Error, fs::read_to_string};
+
+use crate::CipherError;
+
+/// Ordered set of symbols the ciphers operate on. Position `i` of `lower`
+/// and `upper` is the same symbol in both cases; symbols without case
+/// simply appear twice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alphabet {
+    lower: Vec<char>,
+    upper: Vec<char>,
+}
+
+impl Alphabet {
+    pub fn new(lower: Vec<char>, upper: Vec<char>) -> Result<Self, Box<dyn Error>> {
+        if lower.len() < 2 {
+            return Err(CipherError::InvalidInput(
+                "Invalid alphabet (at least 2 symbols needed)".into(),
+            )
+            .into());
+        }
+        if lower.len() != upper.len() {
+            return Err(CipherError::InvalidInput(
+                "Invalid alphabet (lowercase and uppercase lengths differ)".into(),
+            )
+            .into());
+        }
+        let mut positions = HashMap::new();
+        for (i, (&l, &u)) in lower.iter().zip(upper.iter()).enumerate() {
+            for c in [l, u] {
+                if *positions.entry(c).or_insert(i) != i {
+                    return Err(CipherError::InvalidInput(format!(
+                        "Invalid alphabet (duplicate symbol '{}')",
+                        c
+                    ))
+                    .into());
+                }
+            }
+        }
+
+        Ok(Alphabet { lower, upper })
+    }
+
+    /// Builds an alphabet from its lowercase symbols, deriving the uppercase ones.
+    pub fn from_symbols(symbols: &str) -> Result<Self, Box<dyn Error>> {
+        let lower: Vec<char> = symbols.chars().collect();
+        let upper = lower
+            .iter()
+            .map(|&c| {
+                let mut upper = c.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(u), None) => u,
+                    _ => c,
+                }
+            })
+            .collect();
+        Self::new(lower, upper)
+    }
+
+    /// English alphabet (a-z).
+    pub fn latin() -> Self {
+        Self::from_symbols("abcdefghijklmnopqrstuvwxyz").unwrap()
+    }
+
+    /// Polish alphabet extended with q, v and x so that foreign words
+    /// are encrypted too.
+    pub fn polish() -> Self {
+        Self::from_symbols("aąbcćdeęfghijklłmnńoópqrsśtuvwxyzźż").unwrap()
+    }
+
+    /// Reads an alphabet file: the first line lists the lowercase symbols,
+    /// an optional second line the matching uppercase ones.
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = read_to_string(path).map_err(|_| {
+            CipherError::MissingInput(format!("Alphabet file '{}' not found.", path))
+        })?;
+        let mut lines = content.lines();
+        let lower = lines.next().unwrap_or_default();
+        match lines.next().filter(|l| !l.is_empty()) {
+            Some(upper) => Self::new(lower.chars().collect(), upper.chars().collect()),
+            None => Self::from_symbols(lower),
+        }
+    }
+
+    /// Built-in alphabet by name (`latin`, `polish`) or an alphabet file.
+    pub fn load(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "latin" => Ok(Self::latin()),
+            "polish" => Ok(Self::polish()),
+            path => Self::from_file(path),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lower.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lower.is_empty()
+    }
+
+    /// Position of the symbol and whether it was uppercase.
+    pub fn index(&self, c: char) -> Option<(usize, bool)> {
+        if let Some(i) = self.lower.iter().position(|&l| l == c) {
+            return Some((i, false));
+        }
+        self.upper.iter().position(|&u| u == c).map(|i| (i, true))
+    }
+
+    pub fn symbol(&self, i: usize, uppercase: bool) -> char {
+        if uppercase {
+            self.upper[i]
+        } else {
+            self.lower[i]
+        }
+    }
+
+    /// Keyword-mixed alphabet: the symbols of the keyword without repeats,
+    /// then the remaining symbols in order, e.g. "krypto" gives "kryptoabcdefg...".
+    pub fn keyed(&self, keyword: &str) -> Result<Self, Box<dyn Error>> {
+        let mut order: Vec<usize> = Vec::new();
+        for c in keyword.chars() {
+            let (i, _) = self.index(c).ok_or_else(|| {
+                CipherError::InvalidKey(format!("Keyword symbol '{}' not in the alphabet", c))
+            })?;
+            if !order.contains(&i) {
+                order.push(i);
+            }
+        }
+        let rest: Vec<usize> = (0..self.len()).filter(|i| !order.contains(i)).collect();
+        order.extend(rest);
+
+        Self::new(
+            order.iter().map(|&i| self.lower[i]).collect(),
+            order.iter().map(|&i| self.upper[i]).collect(),
+        )
+    }
+
+    /// Replaces every symbol of the alphabet with `f(position)` (mod the
+    /// alphabet size), keeping its case. Other characters are left intact.
+    /// `f` is called for the symbols in the order of the text.
+    pub fn map(&self, text: &str, f: impl FnMut(i32) -> i32) -> String {
+        self.translate(text, self, f)
+    }
+
+    /// Like `map`, but the new symbols are taken from the position `f(position)`
+    /// of the alphabet `to` of the same size, e.g. a mixed one.
+    pub fn translate(&self, text: &str, to: &Alphabet, mut f: impl FnMut(i32) -> i32) -> String {
+        let m = self.len() as i32;
+        text.chars()
+            .map(|c| match self.index(c) {
+                Some((i, uppercase)) => to.symbol(f(i as i32).rem_euclid(m) as usize, uppercase),
+                None => c,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,1151 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    affine,
+    alphabet::Alphabet,
+    autokey, beaufort,
+    bytes::{self, ByteModel},
+    caesar,
+    hill::{self, Matrix},
+    language::Language,
+    monoalphabetic,
+    ngram::Ngrams,
+    playfair::{self, Square},
+    stats, transposition, vigenere, CipherError,
+};
+
+/// Largest column count tried by the columnar transposition attack.
+pub const MAX_COLUMNS: usize = 12;
+
+/// Largest rail count tried by the rail fence attack.
+pub const MAX_RAILS: usize = 50;
+
+/// Column counts up to this one are searched exhaustively.
+const EXHAUSTIVE_COLUMNS: usize = 7;
+
+/// Stands for the plaintext symbols a partial key cannot decrypt.
+pub const UNKNOWN_SYMBOL: char = '?';
+
+/// Largest block size tried by the Hill known-plaintext attack.
+pub const HILL_MAX_SIZE: usize = 5;
+
+/// Shortest cosets whose index of coincidence is trusted in the Vigenère
+/// period search, and how far from random towards the language it has to
+/// be for the cosets to count as single Caesar shifts.
+const VIGENERE_MIN_COSET_LEN: usize = 25;
+const VIGENERE_IOC_SHARE: f64 = 0.6;
+
+/// Temperature steps of the Playfair simulated annealing, the number of key
+/// changes tried at every step and the starting temperature per quadgram,
+/// which falls linearly to zero.
+const PLAYFAIR_STEPS: usize = 20;
+const PLAYFAIR_ITERATIONS: usize = 2000;
+const PLAYFAIR_TEMPERATURE: f64 = 0.03;
+
+/// Statistic used to rank brute-force candidates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scoring {
+    ChiSquared,
+    LogLikelihood,
+}
+
+/// Key recovered from aligned plaintext and ciphertext.
+#[derive(Debug, Clone)]
+pub struct KnownPlaintext<K, T = String> {
+    pub key: K,
+    pub plaintext: T,
+}
+
+/// Offset in the ciphertext at which the crib fits with the given key.
+#[derive(Debug, Clone, Copy)]
+pub struct CribMatch {
+    pub offset: usize,
+    pub key: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CaesarCrib {
+    pub key: i32,
+    pub matches: Vec<CribMatch>,
+    /// Number of distinct keys among the matches.
+    pub candidates: usize,
+    pub plaintext: String,
+}
+
+/// Cipher alphabet of a keyword-mixed cipher recovered from a crib.
+#[derive(Debug, Clone)]
+pub struct MixedAlphabetCrib {
+    /// Offsets in the ciphertext at which the crib fits, the first one is used.
+    pub offsets: Vec<usize>,
+    /// Cipher symbol of every plaintext symbol, `None` where it is unknown.
+    pub table: Vec<Option<char>>,
+    /// Ciphertext decrypted with the known part of the table, with
+    /// `UNKNOWN_SYMBOL` for the rest.
+    pub plaintext: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CaesarCandidate {
+    pub key: i32,
+    pub chi_squared: f64,
+    pub log_likelihood: f64,
+    pub plaintext: String,
+}
+
+/// Brute-force candidate for binary data. The decrypted data is not kept,
+/// as there are many candidates and the files may be large.
+#[derive(Debug, Clone, Copy)]
+pub struct ByteCandidate<K> {
+    pub key: K,
+    pub fitness: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AffineCandidate {
+    pub key: (i32, i32),
+    pub fitness: f64,
+    pub plaintext: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct VigenereAnalysis {
+    pub friedman: f64,
+    /// `(period, votes)` pairs of the Kasiski examination.
+    pub kasiski: Vec<(usize, usize)>,
+    pub period: usize,
+    pub key: String,
+    pub plaintext: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MonoalphabeticSolution {
+    /// Cipher letters for a-z, as used by `monoalphabetic::encrypt`.
+    pub key: String,
+    pub fitness: f64,
+    pub plaintext: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RailFenceCandidate {
+    pub rails: usize,
+    pub fitness: f64,
+    pub plaintext: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnarCandidate {
+    /// Rank of every column, see `transposition::column_order`.
+    pub order: Vec<usize>,
+    pub fitness: f64,
+    pub plaintext: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayfairSolution {
+    pub key: Square,
+    pub fitness: f64,
+    pub plaintext: String,
+}
+
+fn caesar_crib_key(ciphertext: &[char], crib: &[char], alphabet: &Alphabet) -> Option<i32> {
+    let mut key: Option<i32> = None;
+    for (&c, &p) in ciphertext.iter().zip(crib.iter()) {
+        if let (Some((pos_c, _)), Some((pos_p, _))) = (alphabet.index(c), alphabet.index(p)) {
+            let curr_key = (pos_c as i32 - pos_p as i32).rem_euclid(alphabet.len() as i32);
+            if *key.get_or_insert(curr_key) != curr_key {
+                return None;
+            }
+        } else if c != p {
+            return None;
+        }
+    }
+
+    key.filter(|&k| k != 0)
+}
+
+/// Caesar known-plaintext attack with a crib at an unknown position.
+pub fn caesar_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+    alphabet: &Alphabet,
+) -> Result<CaesarCrib, Box<dyn Error>> {
+    let cipher_chars: Vec<char> = ciphertext.chars().collect();
+    let crib: Vec<char> = plaintext.trim_end_matches(['\n', '\r']).chars().collect();
+
+    if crib.is_empty() {
+        return Err(CipherError::InvalidInput("Crib is empty.".into()).into());
+    }
+    if crib.len() > cipher_chars.len() {
+        return Err(CipherError::InvalidInput("Crib is longer than the ciphertext.".into()).into());
+    }
+
+    // Slide the crib over the ciphertext and keep every offset at which
+    // all of its letters are shifted by the same amount.
+    let matches: Vec<CribMatch> = cipher_chars
+        .windows(crib.len())
+        .enumerate()
+        .filter_map(|(offset, window)| {
+            caesar_crib_key(window, &crib, alphabet).map(|key| CribMatch { offset, key })
+        })
+        .collect();
+
+    let mut votes: Vec<(i32, usize)> = Vec::new();
+    for m in &matches {
+        match votes.iter_mut().find(|(k, _)| *k == m.key) {
+            Some((_, count)) => *count += 1,
+            None => votes.push((m.key, 1)),
+        }
+    }
+
+    let key = votes
+        .iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|&(key, _)| key)
+        .ok_or(CipherError::InconsistentCrib(
+            "Unable to find the key.".into(),
+        ))?;
+
+    Ok(CaesarCrib {
+        key,
+        matches,
+        candidates: votes.len(),
+        plaintext: caesar::decrypt(ciphertext, key, alphabet)?,
+    })
+}
+
+/// Cipher position of every plaintext position if the crib fits the
+/// ciphertext window under some one-to-one substitution.
+fn mixed_crib_table(
+    ciphertext: &[char],
+    crib: &[char],
+    alphabet: &Alphabet,
+) -> Option<Vec<Option<usize>>> {
+    let mut table = vec![None; alphabet.len()];
+    for (&c, &p) in ciphertext.iter().zip(crib.iter()) {
+        if let (Some((pos_c, _)), Some((pos_p, _))) = (alphabet.index(c), alphabet.index(p)) {
+            match table[pos_p] {
+                Some(known) if known != pos_c => return None,
+                Some(_) => {}
+                None if table.contains(&Some(pos_c)) => return None,
+                None => table[pos_p] = Some(pos_c),
+            }
+        } else if c != p {
+            return None;
+        }
+    }
+
+    Some(table)
+}
+
+/// Known-plaintext attack on the keyword-mixed Caesar and affine ciphers with
+/// a crib at an unknown position. Both are substitutions, so the crib only
+/// reveals the cipher symbols of its own letters; the table is the mixed
+/// alphabet as seen from the plaintext alphabet, i.e. already shifted.
+pub fn mixed_alphabet_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+    alphabet: &Alphabet,
+) -> Result<MixedAlphabetCrib, Box<dyn Error>> {
+    let cipher_chars: Vec<char> = ciphertext.chars().collect();
+    let crib: Vec<char> = plaintext.trim_end_matches(['\n', '\r']).chars().collect();
+
+    if crib.is_empty() {
+        return Err(CipherError::InvalidInput("Crib is empty.".into()).into());
+    }
+    if crib.len() > cipher_chars.len() {
+        return Err(CipherError::InvalidInput("Crib is longer than the ciphertext.".into()).into());
+    }
+
+    let mut offsets = Vec::new();
+    let mut table = None;
+    for (offset, window) in cipher_chars.windows(crib.len()).enumerate() {
+        if let Some(t) = mixed_crib_table(window, &crib, alphabet) {
+            offsets.push(offset);
+            table.get_or_insert(t);
+        }
+    }
+    let mut table = table.ok_or(CipherError::InconsistentCrib(
+        "The crib does not fit the ciphertext anywhere.".into(),
+    ))?;
+
+    // A single missing pair is determined by the others.
+    if table.iter().filter(|t| t.is_none()).count() == 1 {
+        let missing_plain = table.iter().position(|t| t.is_none()).unwrap();
+        let missing_cipher = (0..alphabet.len()).find(|c| !table.contains(&Some(*c)));
+        table[missing_plain] = missing_cipher;
+    }
+
+    let plaintext = ciphertext
+        .chars()
+        .map(|c| match alphabet.index(c) {
+            Some((pos_c, uppercase)) => table
+                .iter()
+                .position(|&t| t == Some(pos_c))
+                .map_or(UNKNOWN_SYMBOL, |pos_p| alphabet.symbol(pos_p, uppercase)),
+            None => c,
+        })
+        .collect();
+
+    Ok(MixedAlphabetCrib {
+        offsets,
+        table: table
+            .iter()
+            .map(|t| t.map(|pos_c| alphabet.symbol(pos_c, false)))
+            .collect(),
+        plaintext,
+    })
+}
+
+/// Every Caesar key, best candidate first.
+pub fn caesar_brute_force(
+    ciphertext: &str,
+    alphabet: &Alphabet,
+    scoring: Scoring,
+    language: &Language,
+) -> Result<Vec<CaesarCandidate>, Box<dyn Error>> {
+    let expected = language.frequencies();
+    let mut candidates = Vec::new();
+    for key in 1..alphabet.len() as i32 {
+        let plaintext = caesar::decrypt(ciphertext, key, alphabet)?;
+        let letters = stats::letters(&plaintext);
+        candidates.push(CaesarCandidate {
+            key,
+            chi_squared: stats::chi_squared(&letters, &expected),
+            log_likelihood: stats::log_likelihood(&letters, &expected),
+            plaintext,
+        });
+    }
+
+    match scoring {
+        Scoring::ChiSquared => candidates.sort_by(|a, b| a.chi_squared.total_cmp(&b.chi_squared)),
+        Scoring::LogLikelihood => {
+            candidates.sort_by(|a, b| b.log_likelihood.total_cmp(&a.log_likelihood))
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// Affine known-plaintext attack over all aligned symbol pairs.
+pub fn affine_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+    alphabet: &Alphabet,
+) -> Result<KnownPlaintext<(i32, i32)>, Box<dyn Error>> {
+    let m = alphabet.len() as i32;
+    let mut pairs = Vec::new();
+    for (y, x) in ciphertext.chars().zip(plaintext.chars()) {
+        if let (Some((x_pos, _)), Some((y_pos, _))) = (alphabet.index(x), alphabet.index(y)) {
+            pairs.push((x_pos as i32, y_pos as i32));
+        } else if y != x {
+            return Err(CipherError::InconsistentCrib(
+                "Ciphertext char type != plaintext char type.".into(),
+            )
+            .into());
+        }
+    }
+
+    if pairs.len() < 2 {
+        return Err(CipherError::InvalidInput("2 or more character pairs needed.".into()).into());
+    }
+
+    // y1 - y2 = a * (x1 - x2) (mod m), so any pair of pairs with an
+    // invertible plaintext difference determines the key.
+    let (a, b) = pairs
+        .iter()
+        .enumerate()
+        .find_map(|(i, &(x1, y1))| {
+            pairs[i + 1..].iter().find_map(|&(x2, y2)| {
+                let diff_x = (x1 - x2).rem_euclid(m);
+                let diff_y = (y1 - y2).rem_euclid(m);
+                let a = (affine::inverse(diff_x, m)? * diff_y).rem_euclid(m);
+                let b = (y1 - a * x1).rem_euclid(m);
+                Some((a, b))
+            })
+        })
+        .ok_or(CipherError::InconsistentCrib(
+            "No aligned letter pair determines the key uniquely.".into(),
+        ))?;
+
+    if affine::inverse(a, m).is_none() {
+        return Err(CipherError::InconsistentCrib(format!(
+            "Inconsistent crib (recovered 'a' = {} is not invertible).",
+            a
+        ))
+        .into());
+    }
+
+    if let Some((x, y)) = pairs.iter().find(|&&(x, y)| (a * x + b).rem_euclid(m) != y) {
+        return Err(CipherError::InconsistentCrib(format!(
+            "Inconsistent crib (key a={}, b={} maps '{}' to '{}', not '{}').",
+            a,
+            b,
+            alphabet.symbol(*x as usize, false),
+            alphabet.symbol((a * x + b).rem_euclid(m) as usize, false),
+            alphabet.symbol(*y as usize, false)
+        ))
+        .into());
+    }
+
+    Ok(KnownPlaintext {
+        key: (a, b),
+        plaintext: affine::decrypt(ciphertext, a, b, alphabet)?,
+    })
+}
+
+/// Every valid affine key ranked by bigram fitness, best candidate first.
+pub fn affine_brute_force(
+    ciphertext: &str,
+    alphabet: &Alphabet,
+    language: &Language,
+) -> Result<Vec<AffineCandidate>, Box<dyn Error>> {
+    let bigrams = &language.bigrams;
+    let m = alphabet.len() as i32;
+    let mut candidates = Vec::new();
+    for a in (1..m).filter(|&a| affine::gcd(a, m) == 1) {
+        for b in 0..m {
+            let plaintext = affine::decrypt(ciphertext, a, b, alphabet)?;
+            candidates.push(AffineCandidate {
+                key: (a, b),
+                fitness: bigrams.fitness(&stats::letters(&plaintext)),
+                plaintext,
+            });
+        }
+    }
+    candidates.sort_by(|x, y| y.fitness.total_cmp(&x.fitness));
+
+    Ok(candidates)
+}
+
+/// Byte Caesar key recovery from plaintext bytes aligned with the start of the
+/// ciphertext, e.g. a known file header.
+pub fn caesar_bytes_plaintext(
+    ciphertext: &[u8],
+    plaintext: &[u8],
+) -> Result<KnownPlaintext<i32, Vec<u8>>, Box<dyn Error>> {
+    if plaintext.is_empty() {
+        return Err(CipherError::InvalidInput("Crib is empty.".into()).into());
+    }
+
+    let mut shifts = ciphertext
+        .iter()
+        .zip(plaintext)
+        .map(|(&c, &p)| c.wrapping_sub(p));
+    let Some(key) = shifts.next() else {
+        return Err(CipherError::InvalidInput("Ciphertext is empty.".into()).into());
+    };
+    if shifts.any(|s| s != key) {
+        return Err(CipherError::InconsistentCrib(
+            "Inconsistent crib (bytes are shifted by different amounts).".into(),
+        )
+        .into());
+    }
+    if key == 0 {
+        return Err(CipherError::InconsistentCrib("Unable to find the key.".into()).into());
+    }
+
+    Ok(KnownPlaintext {
+        key: key as i32,
+        plaintext: caesar::decrypt_bytes(ciphertext, key as i32)?,
+    })
+}
+
+/// Byte affine key recovery from plaintext bytes aligned with the start of the
+/// ciphertext. Every key is checked against all aligned bytes, so the crib has
+/// to determine it uniquely.
+pub fn affine_bytes_plaintext(
+    ciphertext: &[u8],
+    plaintext: &[u8],
+) -> Result<KnownPlaintext<affine::Key, Vec<u8>>, Box<dyn Error>> {
+    let pairs: Vec<(i32, i32)> = plaintext
+        .iter()
+        .zip(ciphertext)
+        .map(|(&p, &c)| (p as i32, c as i32))
+        .collect();
+    let Some(&(p0, c0)) = pairs.first() else {
+        return Err(CipherError::InvalidInput("Crib is empty.".into()).into());
+    };
+
+    let keys: Vec<(i32, i32)> = (1..256)
+        .step_by(2)
+        .map(|a| (a, (c0 - a * p0).rem_euclid(256)))
+        .filter(|&(a, b)| pairs.iter().all(|&(p, c)| (a * p + b).rem_euclid(256) == c))
+        .collect();
+
+    match keys[..] {
+        [] => Err(CipherError::InconsistentCrib(
+            "Inconsistent crib (no key maps the plaintext to the ciphertext).".into(),
+        )
+        .into()),
+        [(a, b)] => Ok(KnownPlaintext {
+            key: (a, b),
+            plaintext: affine::decrypt_bytes(ciphertext, a, b)?,
+        }),
+        _ => Err(CipherError::InconsistentCrib(format!(
+            "Crib fits {} keys, a longer one is needed.",
+            keys.len()
+        ))
+        .into()),
+    }
+}
+
+/// Every byte Caesar key ranked by the byte model, best candidate first.
+pub fn caesar_bytes_brute_force(
+    ciphertext: &[u8],
+    model: &ByteModel,
+) -> Result<Vec<ByteCandidate<i32>>, Box<dyn Error>> {
+    if ciphertext.is_empty() {
+        return Err(CipherError::InvalidInput("Ciphertext is empty.".into()).into());
+    }
+
+    let counts = bytes::byte_counts(ciphertext);
+    let mut candidates: Vec<ByteCandidate<i32>> = (1..256)
+        .map(|key| ByteCandidate {
+            key,
+            fitness: model.fitness(&counts, |c| c.wrapping_sub(key as u8)),
+        })
+        .collect();
+    candidates.sort_by(|x, y| y.fitness.total_cmp(&x.fitness));
+
+    Ok(candidates)
+}
+
+/// Every byte affine key except the identity ranked by the byte model, best
+/// candidate first.
+pub fn affine_bytes_brute_force(
+    ciphertext: &[u8],
+    model: &ByteModel,
+) -> Result<Vec<ByteCandidate<affine::Key>>, Box<dyn Error>> {
+    if ciphertext.is_empty() {
+        return Err(CipherError::InvalidInput("Ciphertext is empty.".into()).into());
+    }
+
+    let counts = bytes::byte_counts(ciphertext);
+    let mut candidates = Vec::new();
+    for a in (1..256).step_by(2) {
+        let a_inv = affine::inverse(a, 256).unwrap();
+        for b in (0..256).filter(|&b| (a, b) != (1, 0)) {
+            candidates.push(ByteCandidate {
+                key: (a, b),
+                fitness: model.fitness(&counts, |c| (a_inv * (c as i32 - b)).rem_euclid(256) as u8),
+            });
+        }
+    }
+    candidates.sort_by(|x, y| y.fitness.total_cmp(&x.fitness));
+
+    Ok(candidates)
+}
+
+/// Vigenère key recovery from aligned plaintext and ciphertext.
+pub fn vigenere_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+) -> Result<KnownPlaintext<String>, Box<dyn Error>> {
+    let alphabet = Alphabet::latin();
+    let keystream = crib_keystream(ciphertext, plaintext, &alphabet, |c, p| c - p)?;
+    let key = periodic_keyword(&keystream, &alphabet);
+
+    Ok(KnownPlaintext {
+        plaintext: vigenere::decrypt(ciphertext, &key)?,
+        key,
+    })
+}
+
+/// Period of the Vigenère key. The periods whose long enough cosets have an
+/// IoC close to the language are the candidates, ranked by how well their
+/// Caesar shifts fit. Candidates within one key letter of the best fit are
+/// ordered by their Kasiski votes, then by the distance from the Friedman
+/// estimate, which alone decides when no period passes the IoC test.
+fn vigenere_period(
+    letters: &[u8],
+    kasiski: &[(usize, usize)],
+    friedman: f64,
+    language: &Language,
+) -> usize {
+    let max_period = vigenere::max_period(letters.len());
+    // Shorter cosets give an IoC too noisy to tell the periods apart.
+    let ioc_max_period = (letters.len() / VIGENERE_MIN_COSET_LEN).clamp(1, max_period);
+
+    // Cosets of the right period (or its multiples) look like the language,
+    // while a wrong period mixes several alphabets and flattens the IoC.
+    let language_ioc = language.index_of_coincidence();
+    let threshold = stats::RANDOM_IOC + VIGENERE_IOC_SHARE * (language_ioc - stats::RANDOM_IOC);
+    let candidates: Vec<usize> = (1..=ioc_max_period)
+        .filter(|&period| vigenere::coset_ioc(letters, period) >= threshold)
+        .collect();
+
+    // A divisor of the period that shares a key letter passes as well, and
+    // every multiple does, so the candidates are told apart by how well their
+    // Caesar shifts fit, less the log10 of the keys of that length.
+    let log_probs: [f64; 26] = language.frequencies().map(|f| f.max(1e-6).log10());
+    let score = |period: usize| {
+        vigenere::periodic_fitness(letters, period, &log_probs) - period as f64 * 26f64.log10()
+    };
+    let scores: Vec<(usize, f64)> = candidates.into_iter().map(|p| (p, score(p))).collect();
+    let best = scores.iter().map(|&(_, s)| s).fold(f64::MIN, f64::max);
+
+    let votes = |period: usize| {
+        kasiski
+            .iter()
+            .find(|&&(p, _)| p == period)
+            .map_or(0, |&(_, v)| v)
+    };
+    scores
+        .into_iter()
+        .filter(|&(_, s)| s >= best - 26f64.log10())
+        .map(|(p, _)| p)
+        .max_by(|&a, &b| {
+            votes(a).cmp(&votes(b)).then(
+                (friedman - b as f64)
+                    .abs()
+                    .total_cmp(&(friedman - a as f64).abs()),
+            )
+        })
+        .unwrap_or((friedman.round() as usize).clamp(1, max_period))
+}
+
+/// Vigenère ciphertext-only attack: estimates the period with the coset index
+/// of coincidence, the Kasiski examination and the Friedman test (see
+/// `vigenere_period`), then solves every coset as a Caesar shift.
+pub fn vigenere_ciphertext(
+    ciphertext: &str,
+    language: &Language,
+) -> Result<VigenereAnalysis, Box<dyn Error>> {
+    let letters = stats::letters(ciphertext);
+    if letters.is_empty() {
+        return Err(CipherError::InvalidInput("Ciphertext contains no letters.".into()).into());
+    }
+
+    let kasiski = vigenere::kasiski(&letters, vigenere::max_period(letters.len()));
+    let language_ioc = language.index_of_coincidence();
+    let friedman = vigenere::friedman(&letters, language_ioc);
+    let period = vigenere_period(&letters, &kasiski, friedman, language);
+    let expected = language.frequencies();
+
+    let key: String = vigenere::cosets(&letters, period)
+        .iter()
+        .map(|coset| {
+            let shift = (0..26)
+                .map(|shift| {
+                    let shifted: Vec<u8> = coset.iter().map(|&l| (l + 26 - shift) % 26).collect();
+                    (shift, stats::chi_squared(&shifted, &expected))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(shift, _)| shift)
+                .unwrap_or(0);
+            (shift + b'a') as char
+        })
+        .collect();
+
+    Ok(VigenereAnalysis {
+        friedman,
+        kasiski,
+        period,
+        plaintext: vigenere::decrypt(ciphertext, &key)?,
+        key,
+    })
+}
+
+/// Finds `n` blocks whose plaintext matrix is invertible, trying block
+/// combinations in order.
+fn hill_invertible_blocks(blocks: &[(Vec<u8>, Vec<u8>)], n: usize) -> Option<(Matrix, Matrix)> {
+    fn search(
+        blocks: &[(Vec<u8>, Vec<u8>)],
+        n: usize,
+        start: usize,
+        chosen: &mut Vec<usize>,
+    ) -> Option<(Matrix, Matrix)> {
+        if chosen.len() == n {
+            // Blocks are the columns of P and C.
+            let column = |i: usize, cipher: bool| -> Vec<i32> {
+                chosen
+                    .iter()
+                    .map(|&b| {
+                        let block = if cipher { &blocks[b].1 } else { &blocks[b].0 };
+                        block[i] as i32
+                    })
+                    .collect()
+            };
+            let p: Matrix = (0..n).map(|i| column(i, false)).collect();
+            let c: Matrix = (0..n).map(|i| column(i, true)).collect();
+            return hill::inverse(&p).map(|p_inv| (p_inv, c));
+        }
+
+        for b in start..blocks.len() {
+            chosen.push(b);
+            if let Some(found) = search(blocks, n, b + 1, chosen) {
+                return Some(found);
+            }
+            chosen.pop();
+        }
+        None
+    }
+
+    // Bound the combinatorial search, a handful of blocks is almost always enough.
+    let limit = blocks.len().min(4 * n + 4);
+    search(&blocks[..limit], n, 0, &mut Vec::new())
+}
+
+/// Aligned plaintext and ciphertext symbols as `(key, plaintext)` positions,
+/// with the key computed by `key_of(cipher, plain)` mod the alphabet size.
+fn crib_keystream(
+    ciphertext: &str,
+    plaintext: &str,
+    alphabet: &Alphabet,
+    key_of: impl Fn(i32, i32) -> i32,
+) -> Result<Vec<(i32, i32)>, Box<dyn Error>> {
+    let m = alphabet.len() as i32;
+    let mut keystream = Vec::new();
+    for (c, p) in ciphertext.chars().zip(plaintext.chars()) {
+        match (alphabet.index(c), alphabet.index(p)) {
+            (Some((pos_c, _)), Some((pos_p, _))) => {
+                let (pos_c, pos_p) = (pos_c as i32, pos_p as i32);
+                keystream.push((key_of(pos_c, pos_p).rem_euclid(m), pos_p));
+            }
+            _ if c != p => {
+                return Err(CipherError::InconsistentCrib(
+                    "Ciphertext char type != plaintext char type.".into(),
+                )
+                .into());
+            }
+            _ => {}
+        }
+    }
+
+    if keystream.is_empty() {
+        return Err(CipherError::InconsistentCrib("Unable to find the key.".into()).into());
+    }
+    Ok(keystream)
+}
+
+/// The shortest period that reproduces the whole keystream, as a keyword.
+fn periodic_keyword(keystream: &[(i32, i32)], alphabet: &Alphabet) -> String {
+    let shifts: Vec<i32> = keystream.iter().map(|&(k, _)| k).collect();
+    let period = (1..=shifts.len())
+        .find(|&p| (p..shifts.len()).all(|i| shifts[i] == shifts[i % p]))
+        .unwrap_or(shifts.len());
+
+    shifts[..period]
+        .iter()
+        .map(|&k| alphabet.symbol(k as usize, false))
+        .collect()
+}
+
+/// Beaufort known-plaintext attack: k = c + p.
+pub fn beaufort_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+    alphabet: &Alphabet,
+) -> Result<KnownPlaintext<String>, Box<dyn Error>> {
+    let keystream = crib_keystream(ciphertext, plaintext, alphabet, |c, p| c + p)?;
+    let key = periodic_keyword(&keystream, alphabet);
+
+    Ok(KnownPlaintext {
+        plaintext: beaufort::beaufort(ciphertext, &key, alphabet)?,
+        key,
+    })
+}
+
+/// Variant Beaufort known-plaintext attack: k = p - c.
+pub fn variant_beaufort_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+    alphabet: &Alphabet,
+) -> Result<KnownPlaintext<String>, Box<dyn Error>> {
+    let keystream = crib_keystream(ciphertext, plaintext, alphabet, |c, p| p - c)?;
+    let key = periodic_keyword(&keystream, alphabet);
+
+    Ok(KnownPlaintext {
+        plaintext: beaufort::variant_decrypt(ciphertext, &key, alphabet)?,
+        key,
+    })
+}
+
+/// Autokey known-plaintext attack: k = c - p. After the keyword the
+/// keystream repeats the plaintext, so the keyword length is the shortest
+/// lag at which it does. If the crib is not longer than the keyword, the
+/// whole crib keystream is returned and the rest of the keyword stays unknown.
+pub fn autokey_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+    alphabet: &Alphabet,
+) -> Result<KnownPlaintext<String>, Box<dyn Error>> {
+    let keystream = crib_keystream(ciphertext, plaintext, alphabet, |c, p| c - p)?;
+    let len = (1..keystream.len())
+        .find(|&l| (l..keystream.len()).all(|i| keystream[i].0 == keystream[i - l].1))
+        .unwrap_or(keystream.len());
+
+    let key: String = keystream[..len]
+        .iter()
+        .map(|&(k, _)| alphabet.symbol(k as usize, false))
+        .collect();
+
+    Ok(KnownPlaintext {
+        plaintext: autokey::decrypt(ciphertext, &key, alphabet)?,
+        key,
+    })
+}
+
+/// Known-plaintext attack: K = C * P^-1 for `n` aligned blocks with an
+/// invertible plaintext matrix P. Every block size up to `HILL_MAX_SIZE` is tried
+/// and the key has to reproduce all aligned blocks.
+pub fn hill_plaintext(
+    ciphertext: &str,
+    plaintext: &str,
+) -> Result<KnownPlaintext<Matrix>, Box<dyn Error>> {
+    let cipher_letters = stats::letters(ciphertext);
+    let plain_letters = stats::letters(plaintext);
+    let len = cipher_letters.len().min(plain_letters.len());
+
+    for n in 2..=HILL_MAX_SIZE {
+        let blocks: Vec<(Vec<u8>, Vec<u8>)> = plain_letters[..len - len % n]
+            .chunks(n)
+            .zip(cipher_letters[..len - len % n].chunks(n))
+            .map(|(p, c)| (p.to_vec(), c.to_vec()))
+            .collect();
+        if blocks.len() < n || !cipher_letters.len().is_multiple_of(n) {
+            continue;
+        }
+
+        let Some((p_inv, c)) = hill_invertible_blocks(&blocks, n) else {
+            continue;
+        };
+        let key = hill::multiply(&c, &p_inv);
+        if hill::validate(&key).is_err() {
+            continue;
+        }
+
+        let consistent = blocks.iter().all(|(p, c)| hill::transform(p, &key) == *c);
+        if consistent {
+            return Ok(KnownPlaintext {
+                plaintext: hill::decrypt(ciphertext, &key)?,
+                key,
+            });
+        }
+    }
+
+    Err(CipherError::NotInvertible(
+        "Unable to find the key (not enough invertible plaintext blocks).".into(),
+    )
+    .into())
+}
+
+/// Local search gives up after this many swaps without improvement.
+const HILL_CLIMBING_STALL: usize = 2000;
+
+/// Monoalphabetic substitution ciphertext-only attack: hill climbing over
+/// the key by swapping two letters at a time, scored by quadgram fitness,
+/// with `restarts` random restarts. The first climb starts from the key
+/// matching letter frequencies.
+pub fn monoalphabetic_ciphertext(
+    ciphertext: &str,
+    restarts: usize,
+    language: &Language,
+) -> Result<MonoalphabeticSolution, Box<dyn Error>> {
+    let letters = stats::letters(ciphertext);
+    if letters.len() < 4 {
+        return Err(CipherError::InvalidInput(
+            "Ciphertext is too short for the cryptanalysis.".into(),
+        )
+        .into());
+    }
+
+    let quadgrams = &language.quadgrams;
+    let mut rng = rand::thread_rng();
+    let score = |decryption: &[u8]| -> f64 {
+        let plain: Vec<u8> = letters.iter().map(|&l| decryption[l as usize]).collect();
+        quadgrams.fitness(&plain)
+    };
+
+    // Most frequent ciphertext letter -> most frequent plaintext letter, etc.
+    let counts = stats::letter_counts(&letters);
+    let mut cipher_order: Vec<u8> = (0..26).collect();
+    cipher_order.sort_by(|&a, &b| counts[b as usize].cmp(&counts[a as usize]));
+    let freqs = language.frequencies();
+    let mut plain_order: Vec<u8> = (0..26).collect();
+    plain_order.sort_by(|&a, &b| freqs[b as usize].total_cmp(&freqs[a as usize]));
+    let mut frequency_key = vec![0; 26];
+    for (&c, &p) in cipher_order.iter().zip(plain_order.iter()) {
+        frequency_key[c as usize] = p;
+    }
+
+    let mut best: Option<(Vec<u8>, f64)> = None;
+    for restart in 0..restarts.max(1) {
+        let mut decryption = frequency_key.clone();
+        if restart > 0 {
+            decryption.shuffle(&mut rng);
+        }
+        let mut fitness = score(&decryption);
+
+        let mut stall = 0;
+        while stall < HILL_CLIMBING_STALL {
+            let i = rng.gen_range(0..26);
+            let j = rng.gen_range(0..26);
+            if i == j {
+                continue;
+            }
+
+            decryption.swap(i, j);
+            let new_fitness = score(&decryption);
+            if new_fitness > fitness {
+                fitness = new_fitness;
+                stall = 0;
+            } else {
+                decryption.swap(i, j);
+                stall += 1;
+            }
+        }
+
+        if best.as_ref().is_none_or(|(_, f)| fitness > *f) {
+            best = Some((decryption, fitness));
+        }
+    }
+
+    let (decryption, fitness) = best.unwrap();
+    let key: String = monoalphabetic::invert(&decryption)
+        .iter()
+        .map(|&c| (c + b'a') as char)
+        .collect();
+
+    Ok(MonoalphabeticSolution {
+        plaintext: monoalphabetic::decrypt(ciphertext, &key)?,
+        key,
+        fitness,
+    })
+}
+
+fn chars_fitness(chars: &[char], bigrams: &Ngrams) -> f64 {
+    let letters: Vec<u8> = chars
+        .iter()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect();
+    bigrams.fitness(&letters)
+}
+
+/// Rail counts up to `MAX_RAILS` ranked by bigram fitness, best candidate
+/// first. Only the `top` best ones are kept and decrypted.
+pub fn rail_fence_ciphertext(
+    ciphertext: &str,
+    top: Option<usize>,
+    language: &Language,
+) -> Result<Vec<RailFenceCandidate>, Box<dyn Error>> {
+    let bigrams = &language.bigrams;
+    let len = ciphertext.chars().count();
+    if len < 3 {
+        return Err(CipherError::InvalidInput(
+            "Ciphertext is too short for the cryptanalysis.".into(),
+        )
+        .into());
+    }
+
+    let mut ranking = Vec::new();
+    for rails in 2..len.min(MAX_RAILS + 1) {
+        let plaintext = transposition::rail_fence_decrypt(ciphertext, rails)?;
+        ranking.push((rails, bigrams.fitness(&stats::letters(&plaintext))));
+    }
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranking.truncate(top.unwrap_or(usize::MAX).max(1));
+
+    ranking
+        .into_iter()
+        .map(|(rails, fitness)| {
+            Ok(RailFenceCandidate {
+                rails,
+                fitness,
+                plaintext: transposition::rail_fence_decrypt(ciphertext, rails)?,
+            })
+        })
+        .collect()
+}
+
+/// Next lexicographic permutation, false after the last one.
+fn next_permutation(p: &mut [usize]) -> bool {
+    let Some(i) = (1..p.len()).rev().find(|&i| p[i - 1] < p[i]) else {
+        return false;
+    };
+    let j = (i..p.len()).rev().find(|&j| p[j] > p[i - 1]).unwrap();
+    p.swap(i - 1, j);
+    p[i..].reverse();
+    true
+}
+
+/// Columnar transposition ciphertext-only attack. For every column count up
+/// to `MAX_COLUMNS` the column order is searched by bigram fitness:
+/// exhaustively for small counts, by hill climbing over column swaps and
+/// moves with `restarts` random restarts above that. Returns the best order of every column count,
+/// best candidate first.
+pub fn columnar_ciphertext(
+    ciphertext: &str,
+    restarts: usize,
+    language: &Language,
+) -> Result<Vec<ColumnarCandidate>, Box<dyn Error>> {
+    let bigrams = &language.bigrams;
+    let chars: Vec<char> = ciphertext.chars().collect();
+    let max_columns = MAX_COLUMNS.min(chars.len() / 2);
+    if max_columns < 2 {
+        return Err(CipherError::InvalidInput(
+            "Ciphertext is too short for the cryptanalysis.".into(),
+        )
+        .into());
+    }
+
+    let score = |order: &[usize]| {
+        chars_fitness(&transposition::columnar_untranspose(&chars, order), bigrams)
+    };
+    let mut rng = rand::thread_rng();
+    let mut candidates = Vec::new();
+
+    for n in 2..=max_columns {
+        let mut best_order: Vec<usize> = (0..n).collect();
+        let mut best_fitness = score(&best_order);
+
+        if n <= EXHAUSTIVE_COLUMNS {
+            let mut order = best_order.clone();
+            while next_permutation(&mut order) {
+                let fitness = score(&order);
+                if fitness > best_fitness {
+                    best_fitness = fitness;
+                    best_order = order.clone();
+                }
+            }
+        } else {
+            for _ in 0..restarts.max(1) {
+                let mut order: Vec<usize> = (0..n).collect();
+                order.shuffle(&mut rng);
+                let mut fitness = score(&order);
+
+                // Try every swap and every move of a single column until
+                // none of them helps.
+                let mut improved = true;
+                while improved {
+                    improved = false;
+                    for i in 0..n {
+                        for j in 0..n {
+                            if i == j {
+                                continue;
+                            }
+
+                            let mut swapped = order.clone();
+                            swapped.swap(i, j);
+                            let mut moved = order.clone();
+                            let column = moved.remove(i);
+                            moved.insert(j, column);
+
+                            for candidate in [swapped, moved] {
+                                let new_fitness = score(&candidate);
+                                if new_fitness > fitness {
+                                    fitness = new_fitness;
+                                    order = candidate;
+                                    improved = true;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if fitness > best_fitness {
+                    best_fitness = fitness;
+                    best_order = order;
+                }
+            }
+        }
+
+        candidates.push(ColumnarCandidate {
+            plaintext: transposition::columnar_untranspose(&chars, &best_order)
+                .into_iter()
+                .collect(),
+            order: best_order,
+            fitness: best_fitness,
+        });
+    }
+    candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+
+    Ok(candidates)
+}
+
+/// Random change of the key square: mostly a swap of two letters, sometimes
+/// a swap of rows or columns or a reflection of the whole square.
+fn playfair_neighbour(letters: &[u8], rng: &mut impl Rng) -> Vec<u8> {
+    let mut next = letters.to_vec();
+    let (a, b) = (rng.gen_range(0..5), rng.gen_range(0..5));
+    match rng.gen_range(0..50) {
+        0 => (0..5).for_each(|c| next.swap(a * 5 + c, b * 5 + c)),
+        1 => (0..5).for_each(|r| next.swap(r * 5 + a, r * 5 + b)),
+        2 => {
+            for (i, l) in next.iter_mut().enumerate() {
+                *l = letters[(4 - i / 5) * 5 + i % 5];
+            }
+        }
+        3 => {
+            for (i, l) in next.iter_mut().enumerate() {
+                *l = letters[i / 5 * 5 + 4 - i % 5];
+            }
+        }
+        4 => next.reverse(),
+        _ => next.swap(rng.gen_range(0..25), rng.gen_range(0..25)),
+    }
+    next
+}
+
+/// Playfair ciphertext-only attack: simulated annealing on the key square
+/// scored with the quadgrams of `language`. `merge` is the `(from, to)` letter pair
+/// merged in the square, e.g. `('j', 'i')`.
+pub fn playfair_ciphertext(
+    ciphertext: &str,
+    restarts: usize,
+    merge: (char, char),
+    language: &Language,
+) -> Result<PlayfairSolution, Box<dyn Error>> {
+    let letters = stats::letters(ciphertext);
+    if letters.len() < 4 || !letters.len().is_multiple_of(2) {
+        return Err(CipherError::InvalidInput(
+            "Ciphertext must have an even number of at least 4 letters.".into(),
+        )
+        .into());
+    }
+
+    let quadgrams = &language.quadgrams;
+    let mut rng = rand::thread_rng();
+    let initial = Square::from_keyword("", merge)?;
+    let merge = initial.merge();
+    let score = |square: &Square| quadgrams.fitness(&square.transform(&letters, false));
+    let quadgram_count = (letters.len() - 3) as f64;
+
+    let mut best = (initial.clone(), score(&initial));
+    for _ in 0..restarts.max(1) {
+        let mut square_letters = initial.letters().to_vec();
+        square_letters.shuffle(&mut rng);
+        let mut square = Square::new(square_letters, merge)?;
+        let mut fitness = score(&square);
+
+        for step in 0..PLAYFAIR_STEPS {
+            let temperature =
+                PLAYFAIR_TEMPERATURE * (PLAYFAIR_STEPS - step) as f64 / PLAYFAIR_STEPS as f64;
+            for _ in 0..PLAYFAIR_ITERATIONS {
+                let candidate = Square::new(playfair_neighbour(square.letters(), &mut rng), merge)?;
+                let new_fitness = score(&candidate);
+                // Per quadgram, so that the temperature suits any text length.
+                let delta = (new_fitness - fitness) / quadgram_count;
+                if delta > 0.0 || rng.gen::<f64>() < (delta / temperature).exp() {
+                    square = candidate;
+                    fitness = new_fitness;
+                    if fitness > best.1 {
+                        best = (square.clone(), fitness);
+                    }
+                }
+            }
+        }
+    }
+
+    let (key, fitness) = best;
+    Ok(PlayfairSolution {
+        plaintext: playfair::decrypt(ciphertext, &key)?,
+        key,
+        fitness,
+    })
+}
@@ -0,0 +1,51 @@
+// 2024 Paweł Rybak
+
+use std::{collections::VecDeque, error::Error};
+
+use crate::{alphabet::Alphabet, caesar, Cipher};
+
+/// Shifts the letters by the keyword followed by the plaintext itself.
+pub fn encrypt(
+    plaintext: &str,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    let mut keystream: VecDeque<i32> = caesar::keyword_shifts(keyword, alphabet)?.into();
+    Ok(caesar::shift_each(plaintext, alphabet, |p| {
+        keystream.push_back(p);
+        keystream.pop_front().unwrap()
+    }))
+}
+
+/// Every recovered plaintext letter extends the keystream.
+pub fn decrypt(
+    ciphertext: &str,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    let m = alphabet.len() as i32;
+    let mut keystream: VecDeque<i32> = caesar::keyword_shifts(keyword, alphabet)?.into();
+    Ok(caesar::shift_each(ciphertext, alphabet, |c| {
+        let k = keystream.pop_front().unwrap();
+        keystream.push_back((c - k).rem_euclid(m));
+        -k
+    }))
+}
+
+/// Autokey cipher keyed with a priming keyword.
+#[derive(Debug, Clone)]
+pub struct Autokey {
+    pub alphabet: Alphabet,
+}
+
+impl Cipher for Autokey {
+    type Key = String;
+
+    fn encrypt(&self, plaintext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        encrypt(plaintext, key, &self.alphabet)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        decrypt(ciphertext, key, &self.alphabet)
+    }
+}
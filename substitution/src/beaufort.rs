@@ -0,0 +1,76 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+use crate::{alphabet::Alphabet, caesar, Cipher};
+
+/// Shifts the letters by the repeated keyword, in the direction of `sign`.
+fn shift_periodic(text: &str, shifts: &[i32], sign: i32, alphabet: &Alphabet) -> String {
+    let mut keystream = shifts.iter().cycle();
+    caesar::shift_each(text, alphabet, |_| sign * keystream.next().unwrap())
+}
+
+/// c = k - p, so encryption and decryption are the same operation.
+pub fn beaufort(text: &str, keyword: &str, alphabet: &Alphabet) -> Result<String, Box<dyn Error>> {
+    let shifts = caesar::keyword_shifts(keyword, alphabet)?;
+    let mut keystream = shifts.iter().cycle();
+    // k - p = p + (k - 2p)
+    Ok(caesar::shift_each(text, alphabet, |p| {
+        keystream.next().unwrap() - 2 * p
+    }))
+}
+
+/// c = p - k, i.e. Vigenère decryption.
+pub fn variant_encrypt(
+    plaintext: &str,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    let shifts = caesar::keyword_shifts(keyword, alphabet)?;
+    Ok(shift_periodic(plaintext, &shifts, -1, alphabet))
+}
+
+pub fn variant_decrypt(
+    ciphertext: &str,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    let shifts = caesar::keyword_shifts(keyword, alphabet)?;
+    Ok(shift_periodic(ciphertext, &shifts, 1, alphabet))
+}
+
+/// Beaufort cipher keyed with a word.
+#[derive(Debug, Clone)]
+pub struct Beaufort {
+    pub alphabet: Alphabet,
+}
+
+impl Cipher for Beaufort {
+    type Key = String;
+
+    fn encrypt(&self, plaintext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        beaufort(plaintext, key, &self.alphabet)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        beaufort(ciphertext, key, &self.alphabet)
+    }
+}
+
+/// Variant Beaufort cipher keyed with a word.
+#[derive(Debug, Clone)]
+pub struct VariantBeaufort {
+    pub alphabet: Alphabet,
+}
+
+impl Cipher for VariantBeaufort {
+    type Key = String;
+
+    fn encrypt(&self, plaintext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        variant_encrypt(plaintext, key, &self.alphabet)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        variant_decrypt(ciphertext, key, &self.alphabet)
+    }
+}
@@ -0,0 +1,219 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+use crate::{language::Language, CipherError};
+
+/// Share of the letters in a text, the rest being other printable characters.
+pub const LETTER_SHARE: f64 = 0.8;
+
+/// Printable ASCII characters other than letters, plus tab, newline and
+/// carriage return.
+const OTHER_PRINTABLE: f64 = 36.0;
+
+/// Log10 probability of a byte that is neither a letter nor printable ASCII.
+const UNPRINTABLE_LOG_PROB: f64 = -6.0;
+
+/// Byte log-probability table used to measure how plausible decrypted binary
+/// data is, analogous to `Ngrams` for text.
+#[derive(Debug, Clone)]
+pub struct ByteModel {
+    log_probs: Vec<f64>,
+}
+
+pub fn byte_counts(data: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for &b in data {
+        counts[b as usize] += 1;
+    }
+    counts
+}
+
+pub fn is_printable(b: u8) -> bool {
+    b.is_ascii_graphic() || b" \t\n\r".contains(&b)
+}
+
+impl ByteModel {
+    /// Add-one smoothed log10 probabilities, so unseen bytes are not impossible.
+    fn from_counts(counts: &[usize; 256]) -> Self {
+        let total = counts.iter().sum::<usize>() as f64 + 256.0;
+        ByteModel {
+            log_probs: counts
+                .iter()
+                .map(|&c| ((c as f64 + 1.0) / total).log10())
+                .collect(),
+        }
+    }
+
+    /// Learns the model from a sample of the same kind of data as the plaintext,
+    /// e.g. another image or executable.
+    pub fn from_sample(sample: &[u8]) -> Self {
+        Self::from_counts(&byte_counts(sample))
+    }
+
+    /// Parses "XX COUNT" lines, `XX` being a byte in hex.
+    pub fn parse(data: &str) -> Result<Self, Box<dyn Error>> {
+        let mut counts = [0; 256];
+        for line in data.lines().filter(|l| !l.trim().is_empty()) {
+            let parsed = line.split_once(' ').and_then(|(byte, count)| {
+                Some((
+                    u8::from_str_radix(byte, 16).ok()?,
+                    count.trim().parse::<usize>().ok()?,
+                ))
+            });
+            match parsed {
+                Some((byte, count)) => counts[byte as usize] = count,
+                None => {
+                    return Err(CipherError::InvalidInput(format!(
+                        "Invalid byte count line: '{}'",
+                        line
+                    ))
+                    .into())
+                }
+            }
+        }
+        Ok(Self::from_counts(&counts))
+    }
+
+    /// Mixture of executables, PNG images and plain text.
+    pub fn generic() -> Self {
+        Self::parse(include_str!("../data/byte_counts.txt")).unwrap()
+    }
+
+    /// Plain text in the given language: letters by its unigrams regardless
+    /// of case, other printable characters uniformly, anything else unlikely.
+    pub fn text(language: &Language) -> Self {
+        let freqs = language.frequencies();
+        let log_probs = (0..=255u8)
+            .map(|b| {
+                if b.is_ascii_alphabetic() {
+                    let l = (b.to_ascii_lowercase() - b'a') as usize;
+                    (LETTER_SHARE * freqs[l]).max(1e-6).log10()
+                } else if is_printable(b) {
+                    ((1.0 - LETTER_SHARE) / OTHER_PRINTABLE).log10()
+                } else {
+                    UNPRINTABLE_LOG_PROB
+                }
+            })
+            .collect();
+        ByteModel { log_probs }
+    }
+
+    pub fn log_prob(&self, b: u8) -> f64 {
+        self.log_probs[b as usize]
+    }
+
+    /// Log-likelihood of the data whose byte histogram is `counts` after
+    /// every byte `b` is replaced by `decrypt(b)`.
+    pub fn fitness(&self, counts: &[usize; 256], decrypt: impl Fn(u8) -> u8) -> f64 {
+        counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &c)| c > 0)
+            .map(|(b, &c)| c as f64 * self.log_probs[decrypt(b as u8) as usize])
+            .sum()
+    }
+}
+
+/// Longest key tried by the repeating-key XOR attack.
+pub const MAX_KEY_SIZE: usize = 40;
+
+/// Key sizes with the smallest Hamming distances that are solved in full.
+const KEY_SIZE_CANDIDATES: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct RepeatingKey {
+    pub key: Vec<u8>,
+    /// Log-likelihood of the decrypted data under the byte model.
+    pub fitness: f64,
+}
+
+/// XOR with the key repeated over the whole data.
+pub fn repeating_xor(data: &[u8], key: &[u8]) -> Vec<u8> {
+    data.iter()
+        .zip(key.iter().cycle())
+        .map(|(d, k)| d ^ k)
+        .collect()
+}
+
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x ^ y).count_ones())
+        .sum()
+}
+
+/// Average Hamming distance between consecutive blocks of every key size,
+/// per bit of a block, smallest first. Blocks encrypted with the same key
+/// differ only as much as their plaintexts, which for text is less than
+/// random bytes.
+pub fn key_sizes(data: &[u8], max_size: usize) -> Vec<(usize, f64)> {
+    let mut sizes: Vec<(usize, f64)> = (1..=max_size.min(data.len() / 2))
+        .map(|size| {
+            let blocks: Vec<&[u8]> = data.chunks_exact(size).collect();
+            let distance: u32 = blocks
+                .windows(2)
+                .map(|pair| hamming_distance(pair[0], pair[1]))
+                .sum();
+            let bits = (blocks.len() - 1) * size * 8;
+            (size, distance as f64 / bits as f64)
+        })
+        .collect();
+    sizes.sort_by(|a, b| a.1.total_cmp(&b.1));
+    sizes
+}
+
+/// The key byte that makes the bytes most likely under the model.
+pub fn single_byte_key(data: &[u8], model: &ByteModel) -> (u8, f64) {
+    let counts = byte_counts(data);
+    (0..=255u8)
+        .map(|k| (k, model.fitness(&counts, |b| b ^ k)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+/// Solves every key position of the given size as a single-byte XOR.
+pub fn repeating_key(data: &[u8], size: usize, model: &ByteModel) -> RepeatingKey {
+    let (key, fitness): (Vec<u8>, Vec<f64>) = (0..size)
+        .map(|i| {
+            let column: Vec<u8> = data.iter().skip(i).step_by(size).copied().collect();
+            single_byte_key(&column, model)
+        })
+        .unzip();
+
+    RepeatingKey {
+        key,
+        fitness: fitness.iter().sum(),
+    }
+}
+
+/// Repeating-key XOR ciphertext-only attack: the key sizes with the smallest
+/// normalised Hamming distance are solved column by column and the best
+/// decryption wins. Every key byte costs log10(256), otherwise a multiple of
+/// the key size would always fit at least as well. `None` for less than two bytes.
+pub fn repeating_xor_ciphertext(
+    data: &[u8],
+    max_size: usize,
+    model: &ByteModel,
+) -> Option<RepeatingKey> {
+    let penalty = |k: &RepeatingKey| k.fitness - k.key.len() as f64 * 256f64.log10();
+    key_sizes(data, max_size)
+        .iter()
+        .take(KEY_SIZE_CANDIDATES)
+        .map(|&(size, _)| repeating_key(data, size, model))
+        .max_by(|a, b| penalty(a).total_cmp(&penalty(b)))
+        .map(|mut res| {
+            res.key = shortest_period(&res.key).to_vec();
+            res
+        })
+}
+
+/// The key without repetitions: multiples of the key size have Hamming
+/// distances as small as the size itself and solve into the key repeated.
+fn shortest_period(key: &[u8]) -> &[u8] {
+    let period = (1..key.len())
+        .filter(|p| key.len().is_multiple_of(*p))
+        .find(|&p| key.chunks(p).all(|chunk| chunk == &key[..p]))
+        .unwrap_or(key.len());
+    &key[..period]
+}
@@ -2,26 +2,120 @@
 
 use std::error::Error;
 
-pub fn encrypt(plaintext: &str, key: i32) -> Result<String, Box<dyn Error>> {
-    if !(1..=25).contains(&key) {
-        return Err("Invalid key (0 < key < 26)".into());
+use crate::{alphabet::Alphabet, Cipher, CipherError};
+
+pub fn encrypt(plaintext: &str, key: i32, alphabet: &Alphabet) -> Result<String, Box<dyn Error>> {
+    let m = alphabet.len() as i32;
+    if !(1..m).contains(&key) {
+        return Err(CipherError::InvalidKey(format!("Invalid key (0 < key < {})", m)).into());
     }
 
-    let ciphertext: String = plaintext
+    Ok(shift_each(plaintext, alphabet, |_| key))
+}
+
+pub fn decrypt(ciphertext: &str, key: i32, alphabet: &Alphabet) -> Result<String, Box<dyn Error>> {
+    encrypt(ciphertext, alphabet.len() as i32 - key, alphabet)
+}
+
+/// Keyed Caesar: the shifted position is looked up in the alphabet mixed
+/// with `keyword`. Shift 0 is allowed, the mixed alphabet alone is a cipher.
+pub fn encrypt_keyed(
+    plaintext: &str,
+    key: i32,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    check_keyed_shift(key, alphabet)?;
+    let mixed = alphabet.keyed(keyword)?;
+    Ok(alphabet.translate(plaintext, &mixed, |x| x + key))
+}
+
+pub fn decrypt_keyed(
+    ciphertext: &str,
+    key: i32,
+    keyword: &str,
+    alphabet: &Alphabet,
+) -> Result<String, Box<dyn Error>> {
+    check_keyed_shift(key, alphabet)?;
+    let mixed = alphabet.keyed(keyword)?;
+    Ok(mixed.translate(ciphertext, alphabet, |y| y - key))
+}
+
+fn check_keyed_shift(key: i32, alphabet: &Alphabet) -> Result<(), Box<dyn Error>> {
+    let m = alphabet.len() as i32;
+    if !(0..m).contains(&key) {
+        return Err(CipherError::InvalidKey(format!("Invalid key (0 <= key < {})", m)).into());
+    }
+    Ok(())
+}
+
+/// Shift over Z_256 applied to every byte of arbitrary data.
+pub fn encrypt_bytes(data: &[u8], key: i32) -> Result<Vec<u8>, Box<dyn Error>> {
+    if !(1..256).contains(&key) {
+        return Err(CipherError::InvalidKey("Invalid key (0 < key < 256)".into()).into());
+    }
+
+    Ok(data.iter().map(|&x| x.wrapping_add(key as u8)).collect())
+}
+
+pub fn decrypt_bytes(data: &[u8], key: i32) -> Result<Vec<u8>, Box<dyn Error>> {
+    encrypt_bytes(data, 256 - key)
+}
+
+/// Shifts every symbol of the text by its own amount: `shift` gets the
+/// position of the symbol in the alphabet, in the order of the text.
+pub fn shift_each(text: &str, alphabet: &Alphabet, mut shift: impl FnMut(i32) -> i32) -> String {
+    alphabet.map(text, |x| x + shift(x))
+}
+
+/// Positions of the keyword symbols in the alphabet, the shifts of a
+/// keyed polyalphabetic cipher.
+pub fn keyword_shifts(keyword: &str, alphabet: &Alphabet) -> Result<Vec<i32>, Box<dyn Error>> {
+    let shifts: Option<Vec<i32>> = keyword
         .chars()
-        .map(|c| {
-            if c.is_ascii_alphabetic() {
-                let base = if c.is_uppercase() { b'A' } else { b'a' };
-                (((c as u8 - base + key as u8) % 26) + base) as char
-            } else {
-                c
-            }
-        })
+        .map(|c| alphabet.index(c).map(|(i, _)| i as i32))
         .collect();
+    match shifts {
+        Some(s) if !s.is_empty() => Ok(s),
+        _ => Err(CipherError::InvalidKey(
+            "Invalid key (non-empty word of alphabet symbols needed)".into(),
+        )
+        .into()),
+    }
+}
 
-    Ok(ciphertext)
+/// Caesar cipher keyed with the shift.
+#[derive(Debug, Clone)]
+pub struct Caesar {
+    pub alphabet: Alphabet,
 }
 
-pub fn decrypt(ciphertext: &str, key: i32) -> Result<String, Box<dyn Error>> {
-    encrypt(ciphertext, 26 - key)
+impl Cipher for Caesar {
+    type Key = i32;
+
+    fn encrypt(&self, plaintext: &str, key: &i32) -> Result<String, Box<dyn Error>> {
+        encrypt(plaintext, *key, &self.alphabet)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &i32) -> Result<String, Box<dyn Error>> {
+        decrypt(ciphertext, *key, &self.alphabet)
+    }
+}
+
+/// Caesar cipher over a keyword-mixed alphabet, keyed with the keyword and the shift.
+#[derive(Debug, Clone)]
+pub struct KeyedCaesar {
+    pub alphabet: Alphabet,
+}
+
+impl Cipher for KeyedCaesar {
+    type Key = (String, i32);
+
+    fn encrypt(&self, plaintext: &str, key: &(String, i32)) -> Result<String, Box<dyn Error>> {
+        encrypt_keyed(plaintext, key.1, &key.0, &self.alphabet)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &(String, i32)) -> Result<String, Box<dyn Error>> {
+        decrypt_keyed(ciphertext, key.1, &key.0, &self.alphabet)
+    }
 }
@@ -1,17 +1,29 @@
 // 2024 Paweł Rybak
 
-use clap::{ArgGroup, Parser};
+use clap::{ArgGroup, Parser, ValueEnum};
 
 #[derive(Debug, Parser)]
 #[command(group(
     ArgGroup::new("cipher")
-        .required(true)
-        .args(&["caesar", "affine"]),
+        .args(&[
+            "caesar",
+            "affine",
+            "vigenere",
+            "hill",
+            "monoalphabetic",
+            "rail_fence",
+            "columnar",
+            "double_columnar",
+            "playfair",
+            "autokey",
+            "beaufort",
+            "variant_beaufort",
+        ]),
 ))]
 #[command(group(
     ArgGroup::new("mode")
         .required(true)
-        .args(&["encrypt", "decrypt", "plaintext", "ciphertext"]),
+        .args(&["encrypt", "decrypt", "plaintext", "ciphertext", "stats", "identify", "train"]),
 ))]
 pub struct Args {
     /// Caesar cipher
@@ -22,6 +34,46 @@ pub struct Args {
     #[arg(short)]
     pub affine: bool,
 
+    /// Vigenère cipher
+    #[arg(short)]
+    pub vigenere: bool,
+
+    /// Hill cipher
+    #[arg(short = 'H')]
+    pub hill: bool,
+
+    /// Monoalphabetic substitution cipher
+    #[arg(short)]
+    pub monoalphabetic: bool,
+
+    /// Rail fence cipher
+    #[arg(short = 'f')]
+    pub rail_fence: bool,
+
+    /// Columnar transposition cipher
+    #[arg(short = 't')]
+    pub columnar: bool,
+
+    /// Double columnar transposition cipher
+    #[arg(short = 'T')]
+    pub double_columnar: bool,
+
+    /// Playfair cipher
+    #[arg(short)]
+    pub playfair: bool,
+
+    /// Autokey cipher
+    #[arg(short = 'A')]
+    pub autokey: bool,
+
+    /// Beaufort cipher
+    #[arg(short = 'B')]
+    pub beaufort: bool,
+
+    /// Variant Beaufort cipher
+    #[arg(short = 'V')]
+    pub variant_beaufort: bool,
+
     /// Encryption mode
     #[arg(short)]
     pub encrypt: bool,
@@ -37,4 +89,111 @@ pub struct Args {
     /// Ciphertext cryptanalysis
     #[arg(short = 'k')]
     pub ciphertext: bool,
+
+    /// Statistical analysis of the ciphertext (no cipher needed)
+    #[arg(short = 'i', long)]
+    pub stats: bool,
+
+    /// Identify the cipher of the ciphertext and run the matching attack (no cipher needed)
+    #[arg(short = 'I', long)]
+    pub identify: bool,
+
+    /// Train a language model from the letters of a corpus file (no cipher needed)
+    #[arg(long, value_name = "CORPUS")]
+    pub train: Option<String>,
+
+    /// Keep only the N best candidates in ciphertext cryptanalysis
+    #[arg(short = 'n', long, value_name = "N")]
+    pub top: Option<usize>,
+
+    /// Write the best candidate to 'decrypt.txt' and 'key-new.txt'
+    #[arg(short = 'b', long)]
+    pub best: bool,
+
+    /// Alphabet for the Caesar, affine, autokey and Beaufort ciphers: 'latin', 'polish' or a file
+    /// with lowercase symbols on the first line (and optionally uppercase on the second)
+    #[arg(short = 'l', long, value_name = "NAME|FILE", default_value = "latin")]
+    pub alphabet: String,
+
+    /// Language of the plaintext for the cryptanalysis and statistics: 'english', 'polish'
+    /// or a model file written by --train
+    #[arg(short = 'L', long, value_name = "NAME|FILE", default_value = "english")]
+    pub language: String,
+
+    /// Bigrams, trigrams and quadgrams seen fewer times are left out of a trained model
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub min_count: usize,
+
+    /// Generate a random key into 'key.txt' before encrypting (monoalphabetic cipher only)
+    #[arg(short = 'r', long)]
+    pub random_key: bool,
+
+    /// Number of hill-climbing or annealing restarts in ciphertext cryptanalysis
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub restarts: usize,
+
+    /// Keyword-mixed alphabet in known-plaintext cryptanalysis of the Caesar and affine
+    /// ciphers: recover the cipher alphabet instead of the numbers, written as a key
+    /// when complete or as the alphabet and the partial table with '?' on two lines.
+    /// Encryption and decryption use a mixed alphabet when the key starts with a
+    /// keyword, e.g. "krypto 3 5"
+    #[arg(long)]
+    pub keyed: bool,
+
+    /// Letters merged in the Playfair square: the first one is written as the second
+    #[arg(long, value_name = "PAIR", default_value = "ji")]
+    pub merge: String,
+
+    /// Treat the files as raw bytes: Caesar and affine ciphers over Z_256 (a odd)
+    #[arg(long)]
+    pub bytes: bool,
+
+    /// Sample of unencrypted data of the same kind (e.g. another image) for the
+    /// byte frequency model of binary ciphertext cryptanalysis
+    #[arg(long, value_name = "FILE")]
+    pub reference: Option<String>,
+
+    /// Key given inline instead of reading the key file
+    #[arg(long, value_name = "KEY")]
+    pub key: Option<String>,
+
+    /// Key file ('-' for stdin)
+    #[arg(long, value_name = "FILE", default_value = "key.txt")]
+    pub key_file: String,
+
+    /// Plaintext file: encryption input, ranking output of ciphertext cryptanalysis ('-' for stdin/stdout)
+    #[arg(long, value_name = "FILE", default_value = "plain.txt")]
+    pub plain_file: String,
+
+    /// Ciphertext file: encryption output, decryption and cryptanalysis input ('-' for stdin/stdout)
+    #[arg(long, value_name = "FILE", default_value = "crypto.txt")]
+    pub crypto_file: String,
+
+    /// Known plaintext file for known-plaintext cryptanalysis ('-' for stdin)
+    #[arg(long, value_name = "FILE", default_value = "extra.txt")]
+    pub extra_file: String,
+
+    /// Decrypted text file ('-' for stdout)
+    #[arg(long, value_name = "FILE", default_value = "decrypt.txt")]
+    pub decrypt_file: String,
+
+    /// Recovered key file ('-' for stdout)
+    #[arg(long, value_name = "FILE", default_value = "key-new.txt")]
+    pub key_new_file: String,
+
+    /// Trained language model file ('-' for stdout)
+    #[arg(long, value_name = "FILE", default_value = "model.txt")]
+    pub model_file: String,
+
+    /// Statistic used to rank the candidates
+    #[arg(short = 's', long, value_enum, default_value_t = Scoring::ChiSquared)]
+    pub scoring: Scoring,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Scoring {
+    /// Chi-squared distance from the language's letter frequencies
+    ChiSquared,
+    /// Log-likelihood under the language's letter frequencies
+    LogLikelihood,
 }
@@ -0,0 +1,62 @@
+// 2024 Paweł Rybak
+
+use std::{error::Error, fmt};
+
+/// Failures reported by the ciphers and attacks. Every kind has its own
+/// process exit code; 1 is left for errors from outside the crate (I/O)
+/// and 2 for command line usage errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CipherError {
+    /// An input file or other required input is missing.
+    MissingInput(String),
+    /// The key is malformed or out of range.
+    InvalidKey(String),
+    /// The known plaintext does not fit the ciphertext or does not determine the key.
+    InconsistentCrib(String),
+    /// A key parameter has no inverse modulo the alphabet size.
+    NotInvertible(String),
+    /// The text, alphabet or language data cannot be processed.
+    InvalidInput(String),
+    /// The cipher does not support the requested mode.
+    Unsupported(String),
+}
+
+impl CipherError {
+    /// Stable identifier of the error kind, for scripts reading stderr.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CipherError::MissingInput(_) => "missing-input",
+            CipherError::InvalidKey(_) => "invalid-key",
+            CipherError::InconsistentCrib(_) => "inconsistent-crib",
+            CipherError::NotInvertible(_) => "not-invertible",
+            CipherError::InvalidInput(_) => "invalid-input",
+            CipherError::Unsupported(_) => "unsupported",
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CipherError::MissingInput(_) => 3,
+            CipherError::InvalidKey(_) => 4,
+            CipherError::InconsistentCrib(_) => 5,
+            CipherError::NotInvertible(_) => 6,
+            CipherError::InvalidInput(_) => 7,
+            CipherError::Unsupported(_) => 8,
+        }
+    }
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CipherError::MissingInput(msg)
+            | CipherError::InvalidKey(msg)
+            | CipherError::InconsistentCrib(msg)
+            | CipherError::NotInvertible(msg)
+            | CipherError::InvalidInput(msg)
+            | CipherError::Unsupported(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl Error for CipherError {}
@@ -0,0 +1,194 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+use crate::{affine, stats, Cipher, CipherError};
+
+/// Square key matrix over Z_26, row-major.
+pub type Matrix = Vec<Vec<i32>>;
+
+/// Letter appended to fill the final block.
+pub const PADDING: u8 = b'x' - b'a';
+
+/// Builds a square matrix from `n * n` numbers in row-major order.
+pub fn from_numbers(numbers: &[i32]) -> Result<Matrix, Box<dyn Error>> {
+    let n = (1..=numbers.len())
+        .find(|n| n * n >= numbers.len())
+        .unwrap_or(0);
+    if n < 2 || n * n != numbers.len() {
+        return Err(CipherError::InvalidKey(
+            "Invalid key (n * n numbers with n >= 2 needed)".into(),
+        )
+        .into());
+    }
+
+    Ok(numbers
+        .chunks(n)
+        .map(|row| row.iter().map(|x| x.rem_euclid(26)).collect())
+        .collect())
+}
+
+fn minor(m: &Matrix, row: usize, col: usize) -> Matrix {
+    m.iter()
+        .enumerate()
+        .filter(|&(i, _)| i != row)
+        .map(|(_, r)| {
+            r.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != col)
+                .map(|(_, &x)| x)
+                .collect()
+        })
+        .collect()
+}
+
+/// Determinant mod 26 by cofactor expansion along the first row.
+pub fn determinant(m: &Matrix) -> i32 {
+    match m.len() {
+        0 => 1,
+        1 => m[0][0].rem_euclid(26),
+        n => (0..n)
+            .map(|j| {
+                let sign = if j % 2 == 0 { 1 } else { -1 };
+                sign * m[0][j] * determinant(&minor(m, 0, j))
+            })
+            .sum::<i32>()
+            .rem_euclid(26),
+    }
+}
+
+/// https://en.wikipedia.org/wiki/Hill_cipher#Decryption
+pub fn inverse(m: &Matrix) -> Option<Matrix> {
+    let n = m.len();
+    let det_inv = affine::inverse(determinant(m), 26)?;
+
+    // inverse = det^-1 * adj(m), where adj(m)[i][j] = cofactor(j, i)
+    Some(
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let sign = if (i + j) % 2 == 0 { 1 } else { -1 };
+                        (det_inv * sign * determinant(&minor(m, j, i))).rem_euclid(26)
+                    })
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+pub fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    (0..a.len())
+        .map(|i| {
+            (0..b[0].len())
+                .map(|j| {
+                    (0..b.len())
+                        .map(|k| a[i][k] * b[k][j])
+                        .sum::<i32>()
+                        .rem_euclid(26)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Checks that the key is square and invertible mod 26.
+pub fn validate(key: &Matrix) -> Result<(), Box<dyn Error>> {
+    let n = key.len();
+    if n < 2 || key.iter().any(|row| row.len() != n) {
+        return Err(CipherError::InvalidKey(
+            "Invalid key (square matrix of size >= 2 needed)".into(),
+        )
+        .into());
+    }
+    if affine::inverse(determinant(key), 26).is_none() {
+        return Err(CipherError::NotInvertible(
+            "Invalid key (determinant is not invertible mod 26)".into(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Multiplies consecutive blocks of letters (0-25) by the key matrix.
+pub fn transform(letters: &[u8], key: &Matrix) -> Vec<u8> {
+    letters
+        .chunks(key.len())
+        .flat_map(|block| {
+            key.iter()
+                .map(|row| {
+                    let sum: i32 = row.iter().zip(block).map(|(k, &p)| k * p as i32).sum();
+                    sum.rem_euclid(26) as u8
+                })
+                .collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+/// Replaces the letters of `text` with `letters`, keeping the case and the
+/// other characters in place. Surplus letters (padding) go after the last letter.
+fn merge(text: &str, letters: &[u8]) -> String {
+    let mut letters = letters.iter().map(|&l| (l + b'a') as char);
+    let last_letter = text.rfind(|c: char| c.is_ascii_alphabetic());
+    let mut merged = String::new();
+    for (i, c) in text.char_indices() {
+        if c.is_ascii_alphabetic() {
+            let l = letters.next().unwrap();
+            merged.push(if c.is_uppercase() {
+                l.to_ascii_uppercase()
+            } else {
+                l
+            });
+        } else {
+            merged.push(c);
+        }
+        if Some(i) == last_letter {
+            merged.extend(letters.by_ref());
+        }
+    }
+    merged.extend(letters);
+    merged
+}
+
+/// Encrypts the letters in blocks of the key size, padding the final block
+/// with 'x'. The padding letters are appended to the end of the text.
+pub fn encrypt(plaintext: &str, key: &Matrix) -> Result<String, Box<dyn Error>> {
+    validate(key)?;
+    let mut letters = stats::letters(plaintext);
+    while !letters.len().is_multiple_of(key.len()) {
+        letters.push(PADDING);
+    }
+    Ok(merge(plaintext, &transform(&letters, key)))
+}
+
+/// Decrypts the letters in blocks of the key size. Padding is kept.
+pub fn decrypt(ciphertext: &str, key: &Matrix) -> Result<String, Box<dyn Error>> {
+    validate(key)?;
+    let letters = stats::letters(ciphertext);
+    if !letters.len().is_multiple_of(key.len()) {
+        return Err(CipherError::InvalidInput(
+            "Ciphertext length is not a multiple of the key size.".into(),
+        )
+        .into());
+    }
+    let key_inv = inverse(key).ok_or(CipherError::NotInvertible(
+        "Invalid key (matrix could not be inverted)".into(),
+    ))?;
+    Ok(merge(ciphertext, &transform(&letters, &key_inv)))
+}
+
+/// Hill cipher keyed with an invertible matrix.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hill;
+
+impl Cipher for Hill {
+    type Key = Matrix;
+
+    fn encrypt(&self, plaintext: &str, key: &Matrix) -> Result<String, Box<dyn Error>> {
+        encrypt(plaintext, key)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &Matrix) -> Result<String, Box<dyn Error>> {
+        decrypt(ciphertext, key)
+    }
+}
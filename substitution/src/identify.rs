@@ -0,0 +1,221 @@
+// 2024 Paweł Rybak
+
+use std::fmt;
+
+use crate::{
+    affine,
+    bytes::{self, ByteModel, LETTER_SHARE},
+    language::Language,
+    stats, vigenere,
+};
+
+/// Longest repeating XOR key considered by the identification.
+pub const XOR_MAX_KEY: usize = 32;
+
+/// Cipher families told apart by the identification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Family {
+    /// Letters keep their frequencies, only their order changes (or the text
+    /// is not encrypted at all).
+    Transposition,
+    Caesar,
+    Affine,
+    Monoalphabetic,
+    /// Vigenère-like periodic polyalphabetic substitution.
+    Vigenere,
+    RepeatingXor,
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Family::Transposition => "transposition",
+            Family::Caesar => "caesar",
+            Family::Affine => "affine",
+            Family::Monoalphabetic => "monoalphabetic",
+            Family::Vigenere => "vigenere",
+            Family::RepeatingXor => "repeating-xor",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hypothesis {
+    pub family: Family,
+    /// Key period of the Vigenère and XOR hypotheses.
+    pub period: Option<usize>,
+    /// Log10 likelihood per character of the best decryption, less the
+    /// log10 of the number of keys tried. Higher is better.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Identification {
+    pub len: usize,
+    pub letters: usize,
+    /// Share of printable ASCII bytes.
+    pub printable: f64,
+    pub byte_range: (u8, u8),
+    pub ioc: f64,
+    /// Period with the highest average coset IoC.
+    pub best_period: usize,
+    pub best_period_ioc: f64,
+    /// Best hypothesis first.
+    pub hypotheses: Vec<Hypothesis>,
+}
+
+/// Log-likelihood of letters with the histogram `counts` decrypted by `decrypt`.
+fn letters_fitness(
+    counts: &[usize; 26],
+    log_probs: &[f64; 26],
+    decrypt: impl Fn(usize) -> usize,
+) -> f64 {
+    counts
+        .iter()
+        .enumerate()
+        .map(|(c, &n)| n as f64 * log_probs[decrypt(c)])
+        .sum()
+}
+
+/// Sum of the best single-byte XOR key fitness of every coset of the given period.
+fn xor_fitness(data: &[u8], period: usize, model: &ByteModel) -> f64 {
+    (0..period)
+        .map(|offset| {
+            let coset: Vec<u8> = data.iter().skip(offset).step_by(period).copied().collect();
+            let counts = bytes::byte_counts(&coset);
+            (0..=255u8)
+                .map(|k| model.fitness(&counts, |b| b ^ k))
+                .fold(f64::MIN, f64::max)
+        })
+        .sum()
+}
+
+/// Ranks the cipher families that could have produced `data`. Every family
+/// is scored by how well its best key turns the ciphertext into characters
+/// of `language`, penalised by the size of the key space searched, so that
+/// e.g. a Caesar ciphertext is not attributed to the monoalphabetic
+/// substitution, which always fits the letter frequencies at least as well.
+pub fn identify(data: &[u8], language: &Language) -> Identification {
+    let model = ByteModel::text(language);
+    let letter_log_probs: [f64; 26] = language.frequencies().map(|f| f.max(1e-6).log10());
+
+    let letter_bytes: Vec<u8> = data
+        .iter()
+        .filter(|b| b.is_ascii_alphabetic())
+        .map(|b| b.to_ascii_lowercase() - b'a')
+        .collect();
+    let counts = stats::letter_counts(&letter_bytes);
+    let n = data.len().max(1) as f64;
+    let letter_share = LETTER_SHARE.log10() * letter_bytes.len() as f64;
+    let others: f64 = data
+        .iter()
+        .filter(|b| !b.is_ascii_alphabetic())
+        .map(|&b| model.log_prob(b))
+        .sum();
+    // Every letter hypothesis leaves the other characters as they are.
+    let score = |letters_fitness: f64, keys: f64| {
+        (letters_fitness + letter_share + others - keys.log10()) / n
+    };
+
+    let mut hypotheses = Vec::new();
+    hypotheses.push(Hypothesis {
+        family: Family::Transposition,
+        period: None,
+        score: score(letters_fitness(&counts, &letter_log_probs, |c| c), 1.0),
+    });
+
+    let caesar = (1..26)
+        .map(|k| letters_fitness(&counts, &letter_log_probs, |c| (c + 26 - k) % 26))
+        .fold(f64::MIN, f64::max);
+    hypotheses.push(Hypothesis {
+        family: Family::Caesar,
+        period: None,
+        score: score(caesar, 25.0),
+    });
+
+    let multipliers: Vec<i32> = (2..26).filter(|&a| affine::gcd(a, 26) == 1).collect();
+    let affine = multipliers
+        .iter()
+        .flat_map(|&a| (0..26).map(move |b| (a, b)))
+        .map(|(a, b)| {
+            let a_inv = affine::inverse(a, 26).unwrap();
+            letters_fitness(&counts, &letter_log_probs, |c| {
+                (a_inv * (c as i32 - b)).rem_euclid(26) as usize
+            })
+        })
+        .fold(f64::MIN, f64::max);
+    hypotheses.push(Hypothesis {
+        family: Family::Affine,
+        period: None,
+        score: score(affine, 26.0 * multipliers.len() as f64),
+    });
+
+    // The best substitution pairs the letters sorted by frequency.
+    let mut cipher_order: Vec<usize> = (0..26).collect();
+    cipher_order.sort_by(|&a, &b| counts[b].cmp(&counts[a]));
+    let mut plain_order: Vec<usize> = (0..26).collect();
+    plain_order.sort_by(|&a, &b| letter_log_probs[b].total_cmp(&letter_log_probs[a]));
+    let monoalphabetic: f64 = cipher_order
+        .iter()
+        .zip(plain_order.iter())
+        .map(|(&c, &p)| counts[c] as f64 * letter_log_probs[p])
+        .sum();
+    let permutations: f64 = (1..=26).map(|i| i as f64).product();
+    hypotheses.push(Hypothesis {
+        family: Family::Monoalphabetic,
+        period: None,
+        score: score(monoalphabetic, permutations),
+    });
+
+    let max_period = vigenere::max_period(letter_bytes.len());
+    if let Some((period, fitness)) = (2..=max_period)
+        .map(|p| {
+            (
+                p,
+                vigenere::periodic_fitness(&letter_bytes, p, &letter_log_probs),
+            )
+        })
+        .max_by(|a, b| {
+            let keys = |p: usize| 26f64.powi(p as i32).log10();
+            (a.1 - keys(a.0)).total_cmp(&(b.1 - keys(b.0)))
+        })
+    {
+        hypotheses.push(Hypothesis {
+            family: Family::Vigenere,
+            period: Some(period),
+            score: score(fitness, 26f64.powi(period as i32) * max_period as f64),
+        });
+    }
+
+    let max_key = XOR_MAX_KEY.min(data.len() / 2).max(1);
+    let (period, fitness) = (1..=max_key)
+        .map(|p| (p, xor_fitness(data, p, &model) - p as f64 * 256f64.log10()))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+    hypotheses.push(Hypothesis {
+        family: Family::RepeatingXor,
+        period: Some(period),
+        score: (fitness - (max_key as f64).log10()) / n,
+    });
+
+    hypotheses.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let (best_period, best_period_ioc) = (1..=max_period)
+        .map(|p| (p, vigenere::coset_ioc(&letter_bytes, p)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+
+    Identification {
+        len: data.len(),
+        letters: letter_bytes.len(),
+        printable: data.iter().filter(|&&b| bytes::is_printable(b)).count() as f64 / n,
+        byte_range: (
+            data.iter().copied().min().unwrap_or(0),
+            data.iter().copied().max().unwrap_or(0),
+        ),
+        ioc: stats::index_of_coincidence(&letter_bytes),
+        best_period,
+        best_period_ioc,
+        hypotheses,
+    }
+}
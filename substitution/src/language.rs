@@ -0,0 +1,173 @@
+// 2024 Paweł Rybak
+
+use std::{error::Error, fmt::Write, fs::read_to_string};
+
+use crate::{
+    ngram::{self, Ngrams},
+    stats, CipherError,
+};
+
+/// Unigram to quadgram statistics of a language, used to score candidate
+/// decryptions. Only the ASCII letters are modelled, like in `stats::letters`.
+///
+/// Model files list the log10 probabilities of every order in a section:
+///
+/// ```text
+/// # comment
+/// order 1 floor -5.2
+/// a -1.0880
+/// b -1.8262
+/// order 2 floor -6.4
+/// th -1.5302
+/// ```
+#[derive(Debug, Clone)]
+pub struct Language {
+    pub name: String,
+    pub unigrams: Ngrams,
+    pub bigrams: Ngrams,
+    pub trigrams: Ngrams,
+    pub quadgrams: Ngrams,
+}
+
+/// Order, floor and n-gram log-probabilities of a model file section.
+type Section = (usize, f64, Vec<(Vec<u8>, f64)>);
+
+impl Language {
+    pub fn parse(name: &str, data: &str) -> Result<Self, Box<dyn Error>> {
+        let invalid = |line: &str| -> Box<dyn Error> {
+            CipherError::InvalidInput(format!("Invalid language model line: '{}'", line)).into()
+        };
+
+        let mut sections: Vec<Section> = Vec::new();
+        for line in data.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_ascii_whitespace().collect();
+            match fields[..] {
+                ["order", n, "floor", floor] => {
+                    let n: usize = n.parse().map_err(|_| invalid(line))?;
+                    let floor: f64 = floor.parse().map_err(|_| invalid(line))?;
+                    sections.push((n, floor, Vec::new()));
+                }
+                [ngram, log_prob] => {
+                    let log_prob: f64 = log_prob.parse().map_err(|_| invalid(line))?;
+                    let (_, _, entries) = sections.last_mut().ok_or_else(|| invalid(line))?;
+                    entries.push((ngram::parse_ngram(ngram)?, log_prob));
+                }
+                _ => return Err(invalid(line)),
+            }
+        }
+
+        let mut table = |n: usize| -> Result<Ngrams, Box<dyn Error>> {
+            let position = sections.iter().position(|&(order, _, _)| order == n);
+            match position.map(|i| sections.swap_remove(i)) {
+                Some((_, floor, entries)) => Ngrams::from_log_probs(n, floor, &entries),
+                None => Err(CipherError::InvalidInput(format!(
+                    "Language model '{}' has no order {} section",
+                    name, n
+                ))
+                .into()),
+            }
+        };
+
+        Ok(Language {
+            name: name.to_string(),
+            unigrams: table(1)?,
+            bigrams: table(2)?,
+            trigrams: table(3)?,
+            quadgrams: table(4)?,
+        })
+    }
+
+    /// English trained with `--train` on the prose in `data/corpus/english.txt`,
+    /// see `data/README.md`.
+    pub fn english() -> Self {
+        Self::parse("english", include_str!("../data/english.lm")).expect("valid built-in model")
+    }
+
+    /// Polish trained the same way on `data/corpus/polish.txt`. Only the ASCII
+    /// letters of the text count, so this is Polish without the diacritic
+    /// letters rather than with them folded to plain ones.
+    pub fn polish() -> Self {
+        Self::parse("polish", include_str!("../data/polish.lm")).expect("valid built-in model")
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let data = read_to_string(path).map_err(|_| {
+            CipherError::MissingInput(format!("Language model file '{}' not found.", path))
+        })?;
+        Self::parse(path, &data)
+    }
+
+    /// A built-in language ('english' or 'polish') or a model file.
+    pub fn load(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "english" => Ok(Self::english()),
+            "polish" => Ok(Self::polish()),
+            path => Self::from_file(path),
+        }
+    }
+
+    /// Builds a model from the letters of a corpus. N-grams of two or more
+    /// letters seen fewer than `min_count` times are dropped to keep the model small.
+    pub fn train(name: &str, corpus: &str, min_count: usize) -> Result<Self, Box<dyn Error>> {
+        let letters = stats::letters(corpus);
+        if letters.len() < 4 {
+            return Err(
+                CipherError::InvalidInput("Corpus contains too few letters.".into()).into(),
+            );
+        }
+
+        Ok(Language {
+            name: name.to_string(),
+            unigrams: Ngrams::train(&letters, 1, 1),
+            bigrams: Ngrams::train(&letters, 2, min_count),
+            trigrams: Ngrams::train(&letters, 3, min_count),
+            quadgrams: Ngrams::train(&letters, 4, min_count),
+        })
+    }
+
+    /// The model in the file format read by `parse`.
+    pub fn to_model_string(&self) -> String {
+        let mut out = format!("# language model: {}\n", self.name);
+        for table in [
+            &self.unigrams,
+            &self.bigrams,
+            &self.trigrams,
+            &self.quadgrams,
+        ] {
+            writeln!(out, "order {} floor {:.4}", table.n(), table.floor()).unwrap();
+            for (ngram, log_prob) in table.entries() {
+                let ngram: String = ngram.iter().map(|&l| (l + b'a') as char).collect();
+                writeln!(out, "{} {:.4}", ngram, log_prob).unwrap();
+            }
+        }
+        out
+    }
+
+    /// Relative letter frequencies (a-z).
+    pub fn frequencies(&self) -> [f64; 26] {
+        let mut freqs = [0.0; 26];
+        for (l, f) in freqs.iter_mut().enumerate() {
+            *f = 10f64.powf(self.unigrams.log_prob(&[l as u8]));
+        }
+        let total: f64 = freqs.iter().sum();
+        freqs.map(|f| f / total)
+    }
+
+    /// Expected index of coincidence of a text in this language.
+    pub fn index_of_coincidence(&self) -> f64 {
+        self.frequencies().iter().map(|f| f * f).sum()
+    }
+
+    /// Shannon entropy of the letter frequencies, in bits per letter.
+    pub fn entropy(&self) -> f64 {
+        self.frequencies()
+            .iter()
+            .filter(|&&f| f > 0.0)
+            .map(|f| -f * f.log2())
+            .sum()
+    }
+}
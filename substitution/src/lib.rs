@@ -0,0 +1,42 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+pub mod affine;
+pub mod alphabet;
+pub mod attack;
+pub mod autokey;
+pub mod beaufort;
+pub mod bytes;
+pub mod caesar;
+pub mod error;
+pub mod hill;
+pub mod identify;
+pub mod language;
+pub mod monoalphabetic;
+pub mod ngram;
+pub mod playfair;
+pub mod stats;
+pub mod transposition;
+pub mod vigenere;
+
+pub use affine::{Affine, KeyedAffine};
+pub use alphabet::Alphabet;
+pub use autokey::Autokey;
+pub use beaufort::{Beaufort, VariantBeaufort};
+pub use caesar::{Caesar, KeyedCaesar};
+pub use error::CipherError;
+pub use hill::Hill;
+pub use language::Language;
+pub use monoalphabetic::Monoalphabetic;
+pub use playfair::Playfair;
+pub use transposition::{Columnar, DoubleColumnar, RailFence};
+pub use vigenere::Vigenere;
+
+/// Common interface of the ciphers in this crate.
+pub trait Cipher {
+    type Key;
+
+    fn encrypt(&self, plaintext: &str, key: &Self::Key) -> Result<String, Box<dyn Error>>;
+    fn decrypt(&self, ciphertext: &str, key: &Self::Key) -> Result<String, Box<dyn Error>>;
+}
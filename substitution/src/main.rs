@@ -2,21 +2,42 @@
 
 use std::{
     error::Error,
-    fs::{read_to_string, File},
-    io::Write,
-    process,
+    fs::{self, read_to_string, File},
+    io::{self, Read, Write},
+    process::ExitCode,
 };
 
-use clap::Parser;
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use substitution::{
+    affine,
+    attack::{self, ColumnarCandidate, KnownPlaintext, RailFenceCandidate, Scoring},
+    bytes::{self, ByteModel},
+    caesar,
+    hill::{self, Matrix},
+    identify::{self, Family},
+    monoalphabetic,
+    playfair::Square,
+    stats, transposition, Affine, Alphabet, Autokey, Beaufort, Caesar, CipherError, Columnar,
+    DoubleColumnar, Hill, KeyedAffine, KeyedCaesar, Language, Monoalphabetic, Playfair, RailFence,
+    VariantBeaufort, Vigenere,
+};
 
-mod affine;
-mod caesar;
 mod cli;
 
 #[derive(Debug)]
 enum Cipher {
     Caesar,
     Affine,
+    Vigenere,
+    Hill,
+    Monoalphabetic,
+    RailFence,
+    Columnar,
+    DoubleColumnar,
+    Playfair,
+    Autokey,
+    Beaufort,
+    VariantBeaufort,
 }
 
 #[derive(Debug)]
@@ -25,19 +46,80 @@ enum Mode {
     Decrypt,
     Plaintext,
     Ciphertext,
+    Stats,
+    /// Cipher identification followed by the matching attack.
+    Identify,
+    /// Language model training from the corpus file.
+    Train(String),
+}
+
+/// Input and output files, `-` stands for standard input or output.
+#[derive(Debug)]
+struct Files {
+    key: String,
+    plain: String,
+    crypto: String,
+    extra: String,
+    decrypt: String,
+    key_new: String,
+    model: String,
 }
 
 #[derive(Debug)]
 struct Config {
-    cipher: Cipher,
+    /// The statistics, identification and training modes work without a cipher.
+    cipher: Option<Cipher>,
     mode: Mode,
+    top: Option<usize>,
+    best: bool,
+    scoring: Scoring,
+    alphabet: Alphabet,
+    random_key: bool,
+    restarts: usize,
+    keyed: bool,
+    merge: (char, char),
+    language: Language,
+    min_count: usize,
+    key: Option<String>,
+    files: Files,
+    bytes: bool,
+    reference: Option<String>,
 }
 
-fn parse_config(args: cli::Args) -> Config {
-    let cipher = if args.caesar {
-        Cipher::Caesar
+fn parse_config(args: cli::Args) -> Result<Config, Box<dyn Error>> {
+    let cipher = if args.stats || args.identify || args.train.is_some() {
+        None
+    } else if args.caesar {
+        Some(Cipher::Caesar)
+    } else if args.affine {
+        Some(Cipher::Affine)
+    } else if args.vigenere {
+        Some(Cipher::Vigenere)
+    } else if args.hill {
+        Some(Cipher::Hill)
+    } else if args.monoalphabetic {
+        Some(Cipher::Monoalphabetic)
+    } else if args.rail_fence {
+        Some(Cipher::RailFence)
+    } else if args.columnar {
+        Some(Cipher::Columnar)
+    } else if args.double_columnar {
+        Some(Cipher::DoubleColumnar)
+    } else if args.playfair {
+        Some(Cipher::Playfair)
+    } else if args.autokey {
+        Some(Cipher::Autokey)
+    } else if args.beaufort {
+        Some(Cipher::Beaufort)
+    } else if args.variant_beaufort {
+        Some(Cipher::VariantBeaufort)
     } else {
-        Cipher::Affine
+        cli::Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "a cipher is required in this mode",
+            )
+            .exit();
     };
 
     let mode = if args.encrypt {
@@ -46,277 +128,1022 @@ fn parse_config(args: cli::Args) -> Config {
         Mode::Decrypt
     } else if args.plaintext {
         Mode::Plaintext
-    } else {
+    } else if args.ciphertext {
         Mode::Ciphertext
+    } else if args.identify {
+        Mode::Identify
+    } else if let Some(corpus) = args.train {
+        Mode::Train(corpus)
+    } else {
+        Mode::Stats
     };
 
-    Config { cipher, mode }
-}
+    if args.random_key && !matches!(mode, Mode::Encrypt) {
+        return Err(CipherError::InvalidInput(
+            "A random key can only be generated for encryption.".into(),
+        )
+        .into());
+    }
+    if args.random_key && !matches!(cipher, Some(Cipher::Monoalphabetic)) {
+        return Err(CipherError::InvalidInput(
+            "A random key can only be generated for the monoalphabetic cipher.".into(),
+        )
+        .into());
+    }
+    if args.random_key && args.key.is_some() {
+        return Err(CipherError::InvalidInput(
+            "A random key cannot be combined with an inline key.".into(),
+        )
+        .into());
+    }
 
-fn key_tuple() -> Result<(i32, i32), Box<dyn Error>> {
-    let keys = match read_to_string("key.txt") {
-        Ok(msg) => msg,
-        Err(_) => {
-            eprintln!("Error: 'key.txt' file not found.");
-            process::exit(0);
-        }
+    let scoring = match args.scoring {
+        cli::Scoring::ChiSquared => Scoring::ChiSquared,
+        cli::Scoring::LogLikelihood => Scoring::LogLikelihood,
     };
-    let mut keys = keys.split_ascii_whitespace();
-    let a = keys.next();
-    let b = keys.next();
-    let (a, b) = match (a, b) {
-        (Some(aa), Some(bb)) => match (aa.parse(), bb.parse()) {
-            (Ok(aaa), Ok(bbb)) => (aaa, bbb),
-            _ => {
-                eprintln!("Error: Invalid key.");
-                process::exit(0);
-            }
-        },
+
+    let merge = match args.merge.chars().collect::<Vec<char>>()[..] {
+        [from, to] => (from, to),
         _ => {
-            eprintln!("Error: Invalid key.");
-            process::exit(0);
+            return Err(CipherError::InvalidKey(
+                "Invalid letter merge (two letters needed, e.g. 'ji').".into(),
+            )
+            .into())
         }
     };
-    Ok((a, b))
+
+    Ok(Config {
+        cipher,
+        mode,
+        top: args.top,
+        best: args.best,
+        scoring,
+        alphabet: Alphabet::load(&args.alphabet)?,
+        random_key: args.random_key,
+        restarts: args.restarts,
+        keyed: args.keyed,
+        merge,
+        language: Language::load(&args.language)?,
+        min_count: args.min_count,
+        key: args.key,
+        files: Files {
+            key: args.key_file,
+            plain: args.plain_file,
+            crypto: args.crypto_file,
+            extra: args.extra_file,
+            decrypt: args.decrypt_file,
+            key_new: args.key_new_file,
+            model: args.model_file,
+        },
+        bytes: args.bytes,
+        reference: args.reference,
+    })
 }
 
-fn run_caesar_encrypt() -> Result<(), Box<dyn Error>> {
-    let (key, _) = key_tuple()?;
-    let plaintext = match read_to_string("plain.txt") {
-        Ok(msg) => msg,
-        Err(_) => {
-            eprintln!("Error: 'plain.txt' file not found.");
-            process::exit(0);
-        }
-    };
-    let ciphertext = caesar::encrypt(&plaintext, key)?;
-    let mut crypto_file = File::create("crypto.txt")?;
-    crypto_file.write_all(ciphertext.as_bytes())?;
+/// Reads the whole file, or standard input for `-`.
+fn read_file(file_name: &str) -> Result<String, Box<dyn Error>> {
+    if file_name == "-" {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        return Ok(content);
+    }
+    read_to_string(file_name)
+        .map_err(|_| CipherError::MissingInput(format!("'{}' file not found.", file_name)).into())
+}
+
+/// Reads the whole file as raw bytes, or standard input for `-`.
+fn read_bytes(file_name: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if file_name == "-" {
+        let mut content = Vec::new();
+        io::stdin().read_to_end(&mut content)?;
+        return Ok(content);
+    }
+    fs::read(file_name)
+        .map_err(|_| CipherError::MissingInput(format!("'{}' file not found.", file_name)).into())
+}
+
+/// Writes the file, or standard output for `-`.
+fn write_bytes(file_name: &str, content: &[u8]) -> Result<(), Box<dyn Error>> {
+    if file_name == "-" {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(content)?;
+        stdout.flush()?;
+        return Ok(());
+    }
+    let mut file = File::create(file_name)?;
+    file.write_all(content)?;
     Ok(())
 }
 
-fn run_caesar_decrypt() -> Result<(), Box<dyn Error>> {
-    let (key, _) = key_tuple()?;
-    let ciphertext = match read_to_string("crypto.txt") {
-        Ok(msg) => msg,
-        Err(_) => {
-            eprintln!("Error: 'crypto.txt' file not found.");
-            process::exit(0);
-        }
+fn write_file(file_name: &str, content: &str) -> Result<(), Box<dyn Error>> {
+    write_bytes(file_name, content.as_bytes())
+}
+
+/// The key given with `--key`, or the content of the key file.
+fn read_key(config: &Config) -> Result<String, Box<dyn Error>> {
+    match &config.key {
+        Some(key) => Ok(key.clone()),
+        None => read_file(&config.files.key),
+    }
+}
+
+fn invalid_key(config: &Config) -> Box<dyn Error> {
+    let msg = match config.key {
+        Some(_) => "Invalid key given with --key.".to_string(),
+        None => format!("Invalid key in '{}'.", config.files.key),
     };
-    let plaintext = caesar::decrypt(&ciphertext, key)?;
-    let mut decrypt_file = File::create("decrypt.txt")?;
-    decrypt_file.write_all(plaintext.as_bytes())?;
-    Ok(())
+    CipherError::InvalidKey(msg).into()
 }
 
-fn run_caesar_plaintext(
-    ciphertext: &str,
-    plaintext: &str,
-) -> Result<(i32, String), Box<dyn Error>> {
-    if ciphertext.len() != plaintext.len() {
-        return Err("Error: Ciphertext len != plaintext len.".into());
+fn key_tuple(config: &Config) -> Result<(i32, i32), Box<dyn Error>> {
+    match key_keyed(config)? {
+        (None, numbers) => Ok(numbers),
+        (Some(_), _) => Err(invalid_key(config)),
     }
+}
 
-    let mut key: Option<i32> = None;
-    for (c, p) in ciphertext.chars().zip(plaintext.chars()) {
-        if c.is_ascii_alphabetic() && p.is_ascii_alphabetic() {
-            let base_c = if c.is_uppercase() { b'A' } else { b'a' };
-            let base_p = if p.is_uppercase() { b'A' } else { b'a' };
-            let pos_c = (c as u8 - base_c) as i32;
-            let pos_p = (p as u8 - base_p) as i32;
-            let curr_key = (pos_c - pos_p).rem_euclid(26);
+/// The numbers of the Caesar and affine ciphers, optionally preceded by the
+/// keyword of a mixed alphabet, e.g. "krypto 3 5".
+fn key_keyed(config: &Config) -> Result<(Option<String>, affine::Key), Box<dyn Error>> {
+    let keys = read_key(config)?;
+    let mut keys = keys.split_ascii_whitespace().peekable();
+    let keyword = keys
+        .next_if(|k| k.parse::<i32>().is_err())
+        .map(str::to_string);
+    match (keys.next(), keys.next()) {
+        (Some(a), Some(b)) => match (a.parse(), b.parse()) {
+            (Ok(a), Ok(b)) => Ok((keyword, (a, b))),
+            _ => Err(invalid_key(config)),
+        },
+        _ => Err(invalid_key(config)),
+    }
+}
 
-            if let Some(k) = key {
-                if k != curr_key {
-                    return Err("Error: Invalid caesar cipher.".into());
-                }
-            } else {
-                key = Some(curr_key);
-            }
-        } else if c != p {
-            return Err("Error: Ciphertext char type != plaintext char type.".into());
-        }
+fn key_word(config: &Config) -> Result<String, Box<dyn Error>> {
+    let keys = read_key(config)?;
+    match keys.split_ascii_whitespace().next() {
+        Some(k) if k.chars().all(|c| c.is_alphabetic()) => Ok(k.to_string()),
+        _ => Err(invalid_key(config)),
+    }
+}
+
+fn key_rails(config: &Config) -> Result<usize, Box<dyn Error>> {
+    let keys = read_key(config)?;
+    match keys.split_ascii_whitespace().next().map(str::parse) {
+        Some(Ok(r)) => Ok(r),
+        _ => Err(invalid_key(config)),
+    }
+}
+
+fn key_words(config: &Config) -> Result<(String, String), Box<dyn Error>> {
+    let keys = read_key(config)?;
+    let mut keys = keys.split_ascii_whitespace();
+    match (keys.next(), keys.next()) {
+        (Some(first), Some(second)) => Ok((first.to_string(), second.to_string())),
+        _ => Err(invalid_key(config)),
     }
+}
+
+fn key_matrix(config: &Config) -> Result<Matrix, Box<dyn Error>> {
+    let keys = read_key(config)?;
+    let numbers: Vec<i32> = keys
+        .split_ascii_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid_key(config))?;
+    hill::from_numbers(&numbers)
+}
 
-    let key = key.ok_or("Error: Unable to find the key.")?;
-    let decrypted_text = caesar::decrypt(ciphertext, key)?;
+fn key_permutation(config: &Config) -> Result<String, Box<dyn Error>> {
+    if config.random_key && matches!(config.mode, Mode::Encrypt) {
+        let key = monoalphabetic::random_key();
+        write_file(&config.files.key, &format!("{}\n", key))?;
+        return Ok(key);
+    }
 
-    Ok((key, decrypted_text))
+    let keys = read_key(config)?;
+    match keys.split_ascii_whitespace().next() {
+        Some(k) => monoalphabetic::parse_key(k),
+        None => Err(invalid_key(config)),
+    }
 }
 
-fn run_caesar_ciphertext() -> Result<(), Box<dyn Error>> {
-    let ciphertext = match read_to_string("crypto.txt") {
-        Ok(msg) => msg,
-        Err(_) => {
-            eprintln!("Error: 'crypto.txt' file not found.");
-            process::exit(0);
-        }
-    };
+/// A key square written as 25 letters, or a keyword to build one from.
+fn key_square(config: &Config) -> Result<Square, Box<dyn Error>> {
+    let keys = read_key(config)?;
+    if let Ok(square) = Square::from_letters(&keys, config.merge) {
+        return Ok(square);
+    }
+    match keys.split_ascii_whitespace().next() {
+        Some(k) => Square::from_keyword(k, config.merge),
+        None => Err(invalid_key(config)),
+    }
+}
+
+fn run_encrypt<C: substitution::Cipher>(
+    config: &Config,
+    cipher: &C,
+    key: &C::Key,
+) -> Result<(), Box<dyn Error>> {
+    let plaintext = read_file(&config.files.plain)?;
+    let ciphertext = cipher.encrypt(&plaintext, key)?;
+    write_file(&config.files.crypto, &ciphertext)
+}
+
+fn run_decrypt<C: substitution::Cipher>(
+    config: &Config,
+    cipher: &C,
+    key: &C::Key,
+) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_file(&config.files.crypto)?;
+    let plaintext = cipher.decrypt(&ciphertext, key)?;
+    write_file(&config.files.decrypt, &plaintext)
+}
+
+fn run_caesar_plaintext(config: &Config) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_file(&config.files.crypto)?;
+    let plaintext = read_file(&config.files.extra)?;
+    let res = attack::caesar_plaintext(&ciphertext, &plaintext, &config.alphabet)?;
+
+    for m in &res.matches {
+        println!("Offset {}: key {}", m.offset, m.key);
+    }
+    if res.candidates > 1 {
+        println!(
+            "Key {} chosen out of {} candidates",
+            res.key, res.candidates
+        );
+    }
+
+    write_file(&config.files.key_new, &res.key.to_string())?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+fn run_caesar_ciphertext(config: &Config, ciphertext: &str) -> Result<(), Box<dyn Error>> {
+    let mut candidates = attack::caesar_brute_force(
+        ciphertext,
+        &config.alphabet,
+        config.scoring,
+        &config.language,
+    )?;
+    candidates.truncate(config.top.unwrap_or(candidates.len()));
+
     let mut all_cases = String::new();
-    for key in 1..=25 {
-        let decrypted_text = caesar::decrypt(&ciphertext, key)?;
-        all_cases.push_str(&format!("Key {}: {}\n", key, decrypted_text));
+    for c in &candidates {
+        all_cases.push_str(&format!(
+            "Key {} (chi2 = {:.2}, log-likelihood = {:.2}): {}\n",
+            c.key, c.chi_squared, c.log_likelihood, c.plaintext
+        ));
+    }
+    write_file(&config.files.plain, &all_cases)?;
+
+    if config.best {
+        if let Some(best) = candidates.first() {
+            write_file(&config.files.key_new, &best.key.to_string())?;
+            write_file(&config.files.decrypt, &best.plaintext)?;
+        }
     }
-    let mut plain_file = File::create("plain.txt")?;
-    plain_file.write_all(all_cases.as_bytes())?;
+
     Ok(())
 }
 
-fn run_affine_encrypt() -> Result<(), Box<dyn Error>> {
-    let (a, b) = key_tuple()?;
-    let plaintext = match read_to_string("plain.txt") {
-        Ok(msg) => msg,
-        Err(_) => {
-            eprintln!("Error: 'plain.txt' file not found.");
-            process::exit(0);
-        }
+/// Mixed alphabet of the keyed Caesar or affine cipher from a crib. A
+/// complete table is written as a loadable key: the table as the keyword
+/// with the `identity` numbers, which leave it as it is, e.g.
+/// "qwertyuiopasdfghjklzxcvbnm 0 0". A partial table cannot be used as a key
+/// and is written as two lines instead: the alphabet, then the cipher symbol
+/// of every letter with `?` where it is unknown.
+fn run_mixed_alphabet_plaintext(
+    config: &Config,
+    identity: affine::Key,
+) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_file(&config.files.crypto)?;
+    let plaintext = read_file(&config.files.extra)?;
+    let res = attack::mixed_alphabet_plaintext(&ciphertext, &plaintext, &config.alphabet)?;
+
+    if res.offsets.len() > 1 {
+        println!(
+            "Crib fits at offsets {:?}, using offset {}",
+            res.offsets, res.offsets[0]
+        );
+    } else {
+        println!("Crib fits at offset {}", res.offsets[0]);
+    }
+
+    let table: String = res
+        .table
+        .iter()
+        .map(|c| c.unwrap_or(attack::UNKNOWN_SYMBOL))
+        .collect();
+    let unknown: String = res
+        .table
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.is_none())
+        .map(|(i, _)| config.alphabet.symbol(i, false))
+        .collect();
+    println!("Cipher alphabet: {}", table);
+    if unknown.is_empty() {
+        println!("All letters recovered");
+    } else {
+        println!("Unknown letters: {}", unknown);
+    }
+
+    let key = if unknown.is_empty() {
+        format!("{} {} {}\n", table, identity.0, identity.1)
+    } else {
+        let alphabet: String = (0..config.alphabet.len())
+            .map(|i| config.alphabet.symbol(i, false))
+            .collect();
+        println!(
+            "Partial table written to '{}' (alphabet, then cipher symbols)",
+            config.files.key_new
+        );
+        format!("{}\n{}\n", alphabet, table)
     };
-    let ciphertext = affine::encrypt(&plaintext, a, b)?;
-    let mut crypto_file = File::create("crypto.txt")?;
-    crypto_file.write_all(ciphertext.as_bytes())?;
+    write_file(&config.files.key_new, &key)?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+fn run_affine_plaintext(config: &Config) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_file(&config.files.crypto)?;
+    let plaintext = read_file(&config.files.extra)?;
+    let res = attack::affine_plaintext(&ciphertext, &plaintext, &config.alphabet)?;
+    write_file(
+        &config.files.key_new,
+        &format!("{} {}\n", res.key.0, res.key.1),
+    )?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+fn run_affine_ciphertext(config: &Config, ciphertext: &str) -> Result<(), Box<dyn Error>> {
+    let candidates = attack::affine_brute_force(ciphertext, &config.alphabet, &config.language)?;
+
+    let best = &candidates[0];
+    let runner_up = &candidates[1];
+    println!(
+        "Best key: a={}, b={} (fitness = {:.2})",
+        best.key.0, best.key.1, best.fitness
+    );
+    println!(
+        "Margin over runner-up (a={}, b={}): {:.2}",
+        runner_up.key.0,
+        runner_up.key.1,
+        best.fitness - runner_up.fitness
+    );
+
+    write_file(
+        &config.files.key_new,
+        &format!("{} {}\n", best.key.0, best.key.1),
+    )?;
+    write_file(&config.files.decrypt, &best.plaintext)?;
+
+    let mut all_cases = String::new();
+    for c in candidates.iter().take(config.top.unwrap_or(usize::MAX)) {
+        all_cases.push_str(&format!(
+            "Key (a={}, b={}, fitness = {:.2}): {}\n",
+            c.key.0, c.key.1, c.fitness, c.plaintext
+        ));
+    }
+    write_file(&config.files.plain, &all_cases)
+}
+
+/// Known-plaintext attack of a keyword cipher: `attack` recovers the keyword
+/// from the crib aligned with the start of the ciphertext.
+fn run_keyword_plaintext(
+    config: &Config,
+    attack: impl Fn(&str, &str, &Alphabet) -> Result<KnownPlaintext<String>, Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_file(&config.files.crypto)?;
+    let plaintext = read_file(&config.files.extra)?;
+    let res = attack(&ciphertext, &plaintext, &config.alphabet)?;
+    write_file(&config.files.key_new, &format!("{}\n", res.key))?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+fn run_vigenere_ciphertext(config: &Config, ciphertext: &str) -> Result<(), Box<dyn Error>> {
+    let res = attack::vigenere_ciphertext(ciphertext, &config.language)?;
+
+    println!("Friedman estimate: {:.2}", res.friedman);
+    print!("Kasiski factors:");
+    for (period, votes) in res.kasiski.iter().take(5) {
+        print!(" {} ({})", period, votes);
+    }
+    println!();
+    println!("Key length: {}", res.period);
+
+    write_file(&config.files.key_new, &format!("{}\n", res.key))?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+fn run_hill_plaintext(config: &Config) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_file(&config.files.crypto)?;
+    let plaintext = read_file(&config.files.extra)?;
+    let res = attack::hill_plaintext(&ciphertext, &plaintext)?;
+    let key: Vec<String> = res
+        .key
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect();
+    write_file(&config.files.key_new, &format!("{}\n", key.join("\n")))?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+fn run_monoalphabetic_ciphertext(config: &Config, ciphertext: &str) -> Result<(), Box<dyn Error>> {
+    let res = attack::monoalphabetic_ciphertext(ciphertext, config.restarts, &config.language)?;
+    println!("Best key: {} (fitness = {:.2})", res.key, res.fitness);
+    write_file(&config.files.key_new, &format!("{}\n", res.key))?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+fn run_rail_fence_ciphertext(config: &Config, ciphertext: &str) -> Result<(), Box<dyn Error>> {
+    let candidates = attack::rail_fence_ciphertext(ciphertext, config.top, &config.language)?;
+    write_rail_fence_candidates(config, &candidates)
+}
+
+fn write_rail_fence_candidates(
+    config: &Config,
+    candidates: &[RailFenceCandidate],
+) -> Result<(), Box<dyn Error>> {
+    let best = &candidates[0];
+    println!(
+        "Best key: {} rails (fitness = {:.2})",
+        best.rails, best.fitness
+    );
+    write_file(&config.files.key_new, &format!("{}\n", best.rails))?;
+    write_file(&config.files.decrypt, &best.plaintext)?;
+
+    let mut all_cases = String::new();
+    for c in candidates.iter().take(config.top.unwrap_or(usize::MAX)) {
+        all_cases.push_str(&format!(
+            "Key {} (fitness = {:.2}): {}\n",
+            c.rails, c.fitness, c.plaintext
+        ));
+    }
+    write_file(&config.files.plain, &all_cases)
+}
+
+fn run_columnar_ciphertext(config: &Config, ciphertext: &str) -> Result<(), Box<dyn Error>> {
+    let candidates = attack::columnar_ciphertext(ciphertext, config.restarts, &config.language)?;
+    write_columnar_candidates(config, &candidates)
+}
+
+fn write_columnar_candidates(
+    config: &Config,
+    candidates: &[ColumnarCandidate],
+) -> Result<(), Box<dyn Error>> {
+    let best = &candidates[0];
+    let keyword = transposition::order_keyword(&best.order);
+    println!(
+        "Best key: {} ({} columns, fitness = {:.2})",
+        keyword,
+        best.order.len(),
+        best.fitness
+    );
+    write_file(&config.files.key_new, &format!("{}\n", keyword))?;
+    write_file(&config.files.decrypt, &best.plaintext)?;
+
+    let mut all_cases = String::new();
+    for c in candidates.iter().take(config.top.unwrap_or(usize::MAX)) {
+        all_cases.push_str(&format!(
+            "Key {} (fitness = {:.2}): {}\n",
+            transposition::order_keyword(&c.order),
+            c.fitness,
+            c.plaintext
+        ));
+    }
+    write_file(&config.files.plain, &all_cases)
+}
+
+fn run_playfair_ciphertext(config: &Config) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_file(&config.files.crypto)?;
+    let res =
+        attack::playfair_ciphertext(&ciphertext, config.restarts, config.merge, &config.language)?;
+    println!(
+        "Best key (fitness = {:.2}):\n{}",
+        res.fitness,
+        res.key.to_rows()
+    );
+    write_file(&config.files.key_new, &format!("{}\n", res.key.to_rows()))?;
+    write_file(&config.files.decrypt, &res.plaintext)
+}
+
+/// Letter statistics of the ciphertext compared with the selected language.
+fn run_stats(config: &Config) -> Result<(), Box<dyn Error>> {
+    let language = &config.language;
+    let expected = language.frequencies();
+    let ciphertext = read_file(&config.files.crypto)?;
+    let letters = stats::letters(&ciphertext);
+    if letters.is_empty() {
+        return Err(CipherError::InvalidInput("Ciphertext contains no letters.".into()).into());
+    }
+
+    let n = letters.len();
+    let counts = stats::letter_counts(&letters);
+    let max_count = *counts.iter().max().unwrap();
+    println!(
+        "Letters: {} (of {} characters)",
+        n,
+        ciphertext.chars().count()
+    );
+    println!();
+    println!("Letter  Count      %  Language %");
+    for (i, &count) in counts.iter().enumerate() {
+        let percent = 100.0 * count as f64 / n as f64;
+        println!(
+            "{:>6} {:>6} {:>6.2} {:>11.2}  {}",
+            (b'a' + i as u8) as char,
+            count,
+            percent,
+            100.0 * expected[i],
+            "#".repeat(count * 40 / max_count)
+        );
+    }
+
+    println!();
+    println!(
+        "Index of coincidence: {:.4} ({} {:.4}, random {:.4})",
+        stats::index_of_coincidence(&letters),
+        language.name,
+        language.index_of_coincidence(),
+        stats::RANDOM_IOC
+    );
+    println!(
+        "Entropy: {:.3} bits/letter ({} {:.2}, random {:.2})",
+        stats::entropy(&counts),
+        language.name,
+        language.entropy(),
+        26f64.log2()
+    );
+    println!(
+        "Chi-squared against {}: {:.2}",
+        language.name,
+        stats::chi_squared(&letters, &expected)
+    );
+    println!(
+        "Chi-squared of sorted frequencies against {}: {:.2}",
+        language.name,
+        stats::sorted_chi_squared(&letters, &expected)
+    );
+
+    let top = config.top.unwrap_or(10);
+    for (size, name) in [(1, "Unigrams"), (2, "Bigrams"), (3, "Trigrams")] {
+        let ngrams: Vec<String> = stats::top_ngrams(&letters, size, top)
+            .into_iter()
+            .map(|(ngram, count)| format!("{} {}", ngram, count))
+            .collect();
+        println!("{}: {}", name, ngrams.join(", "));
+    }
+
     Ok(())
 }
 
-fn run_affine_decrypt() -> Result<(), Box<dyn Error>> {
-    let (a, b) = key_tuple()?;
-    let ciphertext = match read_to_string("crypto.txt") {
-        Ok(msg) => msg,
-        Err(_) => {
-            eprintln!("Error: 'crypto.txt' file not found.");
-            process::exit(0);
+/// Ranks the cipher hypotheses for the ciphertext and runs the attack on the
+/// most likely one.
+fn run_identify(config: &Config) -> Result<(), Box<dyn Error>> {
+    let data = read_bytes(&config.files.crypto)?;
+    let res = identify::identify(&data, &config.language);
+
+    println!(
+        "Characters: {}, letters: {}, printable: {:.1}%, bytes 0x{:02x}-0x{:02x}",
+        res.len,
+        res.letters,
+        100.0 * res.printable,
+        res.byte_range.0,
+        res.byte_range.1
+    );
+    println!(
+        "Index of coincidence: {:.4} ({} {:.4}, random {:.4})",
+        res.ioc,
+        config.language.name,
+        config.language.index_of_coincidence(),
+        stats::RANDOM_IOC
+    );
+    println!(
+        "Best coset IoC: {:.4} at period {}",
+        res.best_period_ioc, res.best_period
+    );
+    println!();
+    for (i, h) in res.hypotheses.iter().enumerate() {
+        let period = h
+            .period
+            .map(|p| format!(" (period {})", p))
+            .unwrap_or_default();
+        println!("{:>2}. {}{}: {:.4}", i + 1, h.family, period, h.score);
+    }
+    println!();
+
+    let ciphertext = String::from_utf8_lossy(&data);
+    let best = &res.hypotheses[0];
+    match best.family {
+        Family::Caesar => run_caesar_ciphertext(config, &ciphertext),
+        Family::Affine => run_affine_ciphertext(config, &ciphertext),
+        Family::Monoalphabetic => run_monoalphabetic_ciphertext(config, &ciphertext),
+        Family::Vigenere => run_vigenere_ciphertext(config, &ciphertext),
+        Family::Transposition => {
+            let rail_fence =
+                attack::rail_fence_ciphertext(&ciphertext, config.top, &config.language)?;
+            let columnar =
+                attack::columnar_ciphertext(&ciphertext, config.restarts, &config.language)?;
+            if rail_fence[0].fitness >= columnar[0].fitness {
+                println!("Rail fence fits better than columnar transposition");
+                write_rail_fence_candidates(config, &rail_fence)
+            } else {
+                println!("Columnar transposition fits better than rail fence");
+                write_columnar_candidates(config, &columnar)
+            }
         }
-    };
-    let plaintext = affine::decrypt(&ciphertext, a, b)?;
-    let mut decrypt_file = File::create("decrypt.txt")?;
-    decrypt_file.write_all(plaintext.as_bytes())?;
-    Ok(())
+        Family::RepeatingXor => run_repeating_xor_ciphertext(config, &data),
+    }
 }
 
-fn run_affine_plaintext(ciphertext: &str, plaintext: &str) -> Result<(i32, i32), Box<dyn Error>> {
-    if ciphertext.len() < 2 || plaintext.len() < 2 {
-        return Err("Error: 2 or more character pairs needed.".into());
+/// Repeating-key XOR ciphertext-only attack, with the key size estimated
+/// from the Hamming distances.
+fn run_repeating_xor_ciphertext(config: &Config, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    print!("Key sizes by Hamming distance:");
+    for (size, distance) in bytes::key_sizes(data, identify::XOR_MAX_KEY).iter().take(5) {
+        print!(" {} ({:.3})", size, distance);
     }
+    println!();
 
-    let (x1, x2) = (
-        plaintext.chars().nth(0).unwrap(),
-        plaintext.chars().nth(1).unwrap(),
+    let res = bytes::repeating_xor_ciphertext(
+        data,
+        identify::XOR_MAX_KEY,
+        &ByteModel::text(&config.language),
+    )
+    .ok_or(CipherError::InvalidInput(
+        "Ciphertext is too short for the cryptanalysis.".into(),
+    ))?;
+    println!(
+        "Best key ({} bytes): {} (hex {})",
+        res.key.len(),
+        String::from_utf8_lossy(&res.key).escape_debug(),
+        res.key
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
     );
-    let (y1, y2) = (
-        ciphertext.chars().nth(0).unwrap(),
-        ciphertext.chars().nth(1).unwrap(),
+
+    write_bytes(&config.files.decrypt, &bytes::repeating_xor(data, &res.key))?;
+    write_bytes(&config.files.key_new, &res.key)
+}
+
+/// Language model of the letters of the corpus, in the format read by `--language`.
+fn run_train(config: &Config, corpus: &str) -> Result<(), Box<dyn Error>> {
+    let text = read_file(corpus)?;
+    let language = Language::train(corpus, &text, config.min_count)?;
+    println!(
+        "Letters: {}, index of coincidence: {:.4}, entropy: {:.3} bits/letter",
+        stats::letters(&text).len(),
+        language.index_of_coincidence(),
+        language.entropy()
     );
+    write_file(&config.files.model, &language.to_model_string())
+}
 
-    let base_x1 = if x1.is_uppercase() { b'A' } else { b'a' };
-    let base_y1 = if y1.is_uppercase() { b'A' } else { b'a' };
-    let base_x2 = if x2.is_uppercase() { b'A' } else { b'a' };
-    let base_y2 = if y2.is_uppercase() { b'A' } else { b'a' };
+/// Byte model learned from the `--reference` sample, or the built-in one.
+fn byte_model(config: &Config) -> Result<ByteModel, Box<dyn Error>> {
+    match &config.reference {
+        Some(file_name) => Ok(ByteModel::from_sample(&read_bytes(file_name)?)),
+        None => Ok(ByteModel::generic()),
+    }
+}
 
-    let x1_pos = (x1 as u8 - base_x1) as i32;
-    let y1_pos = (y1 as u8 - base_y1) as i32;
-    let x2_pos = (x2 as u8 - base_x2) as i32;
-    let y2_pos = (y2 as u8 - base_y2) as i32;
+fn run_caesar_bytes_ciphertext(config: &Config) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_bytes(&config.files.crypto)?;
+    let candidates = attack::caesar_bytes_brute_force(&ciphertext, &byte_model(config)?)?;
 
-    let diff_x = (x1_pos - x2_pos).rem_euclid(26);
-    let diff_y = (y1_pos - y2_pos).rem_euclid(26);
+    let best = &candidates[0];
+    println!("Best key: {} (fitness = {:.2})", best.key, best.fitness);
+    write_file(&config.files.key_new, &format!("{}\n", best.key))?;
+    write_bytes(
+        &config.files.decrypt,
+        &caesar::decrypt_bytes(&ciphertext, best.key)?,
+    )?;
 
-    let a = affine::inverse(diff_x, 26)
-        .ok_or("Invalid 'a' parameter ('a' could not be inverted)")?
-        * diff_y;
+    let mut all_cases = String::new();
+    for c in candidates.iter().take(config.top.unwrap_or(usize::MAX)) {
+        all_cases.push_str(&format!("Key {} (fitness = {:.2})\n", c.key, c.fitness));
+    }
+    write_file(&config.files.plain, &all_cases)
+}
 
-    let a = a.rem_euclid(26);
-    let b = (y1_pos - a * x1_pos).rem_euclid(26);
-    Ok((a, b))
+fn run_affine_bytes_ciphertext(config: &Config) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_bytes(&config.files.crypto)?;
+    let candidates = attack::affine_bytes_brute_force(&ciphertext, &byte_model(config)?)?;
+
+    let best = &candidates[0];
+    println!(
+        "Best key: a={}, b={} (fitness = {:.2})",
+        best.key.0, best.key.1, best.fitness
+    );
+    write_file(
+        &config.files.key_new,
+        &format!("{} {}\n", best.key.0, best.key.1),
+    )?;
+    write_bytes(
+        &config.files.decrypt,
+        &affine::decrypt_bytes(&ciphertext, best.key.0, best.key.1)?,
+    )?;
+
+    let mut all_cases = String::new();
+    for c in candidates.iter().take(config.top.unwrap_or(usize::MAX)) {
+        all_cases.push_str(&format!(
+            "Key (a={}, b={}, fitness = {:.2})\n",
+            c.key.0, c.key.1, c.fitness
+        ));
+    }
+    write_file(&config.files.plain, &all_cases)
 }
 
-fn run_affine_ciphertext() -> Result<(), Box<dyn Error>> {
-    let ciphertext = match read_to_string("crypto.txt") {
-        Ok(msg) => msg,
-        Err(_) => {
-            eprintln!("Error: 'crypto.txt' file not found.");
-            process::exit(0);
+/// Caesar and affine ciphers over Z_256, applied to raw file bytes.
+fn run_bytes(config: &Config) -> Result<(), Box<dyn Error>> {
+    match (&config.cipher, &config.mode) {
+        (Some(Cipher::Caesar), Mode::Encrypt) => {
+            let plaintext = read_bytes(&config.files.plain)?;
+            let ciphertext = caesar::encrypt_bytes(&plaintext, key_tuple(config)?.0)?;
+            write_bytes(&config.files.crypto, &ciphertext)?;
         }
-    };
-    let mut all_cases = String::new();
-    for a in 1..=25 {
-        for b in 0..=25 {
-            if let Ok(decrypted_text) = affine::decrypt(&ciphertext, a, b) {
-                all_cases.push_str(&format!("Key (a={}, b={}): {}\n", a, b, decrypted_text));
-            }
+        (Some(Cipher::Caesar), Mode::Decrypt) => {
+            let ciphertext = read_bytes(&config.files.crypto)?;
+            let plaintext = caesar::decrypt_bytes(&ciphertext, key_tuple(config)?.0)?;
+            write_bytes(&config.files.decrypt, &plaintext)?;
+        }
+        (Some(Cipher::Caesar), Mode::Plaintext) => {
+            let ciphertext = read_bytes(&config.files.crypto)?;
+            let plaintext = read_bytes(&config.files.extra)?;
+            let res = attack::caesar_bytes_plaintext(&ciphertext, &plaintext)?;
+            write_file(&config.files.key_new, &format!("{}\n", res.key))?;
+            write_bytes(&config.files.decrypt, &res.plaintext)?;
+        }
+        (Some(Cipher::Caesar), Mode::Ciphertext) => {
+            run_caesar_bytes_ciphertext(config)?;
+        }
+        (Some(Cipher::Affine), Mode::Encrypt) => {
+            let plaintext = read_bytes(&config.files.plain)?;
+            let (a, b) = key_tuple(config)?;
+            write_bytes(
+                &config.files.crypto,
+                &affine::encrypt_bytes(&plaintext, a, b)?,
+            )?;
+        }
+        (Some(Cipher::Affine), Mode::Decrypt) => {
+            let ciphertext = read_bytes(&config.files.crypto)?;
+            let (a, b) = key_tuple(config)?;
+            write_bytes(
+                &config.files.decrypt,
+                &affine::decrypt_bytes(&ciphertext, a, b)?,
+            )?;
+        }
+        (Some(Cipher::Affine), Mode::Plaintext) => {
+            let ciphertext = read_bytes(&config.files.crypto)?;
+            let plaintext = read_bytes(&config.files.extra)?;
+            let res = attack::affine_bytes_plaintext(&ciphertext, &plaintext)?;
+            write_file(
+                &config.files.key_new,
+                &format!("{} {}\n", res.key.0, res.key.1),
+            )?;
+            write_bytes(&config.files.decrypt, &res.plaintext)?;
+        }
+        (Some(Cipher::Affine), Mode::Ciphertext) => {
+            run_affine_bytes_ciphertext(config)?;
+        }
+        _ => {
+            return Err(CipherError::Unsupported(
+                "Binary mode is only supported for the Caesar and affine ciphers.".into(),
+            )
+            .into());
         }
     }
-    let mut plain_file = File::create("plain.txt")?;
-    plain_file.write_all(all_cases.as_bytes())?;
+
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args = cli::Args::parse();
-    let config = parse_config(args);
-
-    match (config.cipher, config.mode) {
-        (Cipher::Caesar, Mode::Encrypt) => {
-            run_caesar_encrypt()?;
-        }
-        (Cipher::Caesar, Mode::Decrypt) => {
-            run_caesar_decrypt()?;
-        }
-        (Cipher::Caesar, Mode::Plaintext) => {
-            let ciphertext = match read_to_string("crypto.txt") {
-                Ok(msg) => msg,
-                Err(_) => {
-                    eprintln!("Error: 'crypto.txt' file not found.");
-                    process::exit(0);
-                }
-            };
-            let plaintext = match read_to_string("extra.txt") {
-                Ok(msg) => msg,
-                Err(_) => {
-                    eprintln!("Error: 'extra.txt' file not found.");
-                    process::exit(0);
-                }
-            };
-            let res = run_caesar_plaintext(&ciphertext, &plaintext)?;
-            let mut key_new_file = File::create("key-new.txt")?;
-            key_new_file.write_all(res.0.to_string().as_bytes())?;
-            let mut decrypt_file = File::create("decrypt.txt")?;
-            decrypt_file.write_all(res.1.as_bytes())?;
-        }
-        (Cipher::Caesar, Mode::Ciphertext) => {
-            run_caesar_ciphertext()?;
-        }
-        (Cipher::Affine, Mode::Encrypt) => {
-            run_affine_encrypt()?;
-        }
-        (Cipher::Affine, Mode::Decrypt) => {
-            run_affine_decrypt()?;
-        }
-        (Cipher::Affine, Mode::Plaintext) => {
-            let ciphertext = match read_to_string("crypto.txt") {
-                Ok(msg) => msg,
-                Err(_) => {
-                    eprintln!("Error: 'crypto.txt' file not found.");
-                    process::exit(0);
-                }
-            };
-            let plaintext = match read_to_string("extra.txt") {
-                Ok(msg) => msg,
-                Err(_) => {
-                    eprintln!("Error: 'extra.txt' file not found.");
-                    process::exit(0);
-                }
-            };
-            let keys = run_affine_plaintext(&ciphertext, &plaintext)?;
-            let decrypted_text = affine::decrypt(&ciphertext, keys.0, keys.1)?;
-            let mut key_new_file = File::create("key-new.txt")?;
-            key_new_file.write_all(format!("{} {}\n", keys.0, keys.1).as_bytes())?;
-            let mut decrypt_file = File::create("decrypt.txt")?;
-            decrypt_file.write_all(decrypted_text.as_bytes())?;
-        }
-        (Cipher::Affine, Mode::Ciphertext) => {
-            run_affine_ciphertext()?;
+fn run(args: cli::Args) -> Result<(), Box<dyn Error>> {
+    let config = parse_config(args)?;
+    match &config.mode {
+        Mode::Stats => return run_stats(&config),
+        Mode::Identify => return run_identify(&config),
+        Mode::Train(corpus) => return run_train(&config, corpus),
+        _ => {}
+    }
+    if config.bytes {
+        return run_bytes(&config);
+    }
+
+    let caesar = Caesar {
+        alphabet: config.alphabet.clone(),
+    };
+    let affine = Affine {
+        alphabet: config.alphabet.clone(),
+    };
+    let keyed_caesar = KeyedCaesar {
+        alphabet: config.alphabet.clone(),
+    };
+    let keyed_affine = KeyedAffine {
+        alphabet: config.alphabet.clone(),
+    };
+    let autokey = Autokey {
+        alphabet: config.alphabet.clone(),
+    };
+    let beaufort = Beaufort {
+        alphabet: config.alphabet.clone(),
+    };
+    let variant_beaufort = VariantBeaufort {
+        alphabet: config.alphabet.clone(),
+    };
+
+    match (&config.cipher, &config.mode) {
+        (Some(Cipher::Caesar), Mode::Encrypt) => match key_keyed(&config)? {
+            (Some(keyword), (key, _)) => run_encrypt(&config, &keyed_caesar, &(keyword, key))?,
+            (None, (key, _)) => run_encrypt(&config, &caesar, &key)?,
+        },
+        (Some(Cipher::Caesar), Mode::Decrypt) => match key_keyed(&config)? {
+            (Some(keyword), (key, _)) => run_decrypt(&config, &keyed_caesar, &(keyword, key))?,
+            (None, (key, _)) => run_decrypt(&config, &caesar, &key)?,
+        },
+        (Some(Cipher::Caesar), Mode::Plaintext) if config.keyed => {
+            run_mixed_alphabet_plaintext(&config, (0, 0))?;
+        }
+        (Some(Cipher::Caesar), Mode::Plaintext) => {
+            run_caesar_plaintext(&config)?;
+        }
+        (Some(Cipher::Caesar), Mode::Ciphertext) => {
+            run_caesar_ciphertext(&config, &read_file(&config.files.crypto)?)?;
+        }
+        (Some(Cipher::Affine), Mode::Encrypt) => match key_keyed(&config)? {
+            (Some(keyword), key) => run_encrypt(&config, &keyed_affine, &(keyword, key))?,
+            (None, key) => run_encrypt(&config, &affine, &key)?,
+        },
+        (Some(Cipher::Affine), Mode::Decrypt) => match key_keyed(&config)? {
+            (Some(keyword), key) => run_decrypt(&config, &keyed_affine, &(keyword, key))?,
+            (None, key) => run_decrypt(&config, &affine, &key)?,
+        },
+        (Some(Cipher::Affine), Mode::Plaintext) if config.keyed => {
+            run_mixed_alphabet_plaintext(&config, (1, 0))?;
+        }
+        (Some(Cipher::Affine), Mode::Plaintext) => {
+            run_affine_plaintext(&config)?;
+        }
+        (Some(Cipher::Affine), Mode::Ciphertext) => {
+            run_affine_ciphertext(&config, &read_file(&config.files.crypto)?)?;
+        }
+        (Some(Cipher::Vigenere), Mode::Encrypt) => {
+            run_encrypt(&config, &Vigenere, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Vigenere), Mode::Decrypt) => {
+            run_decrypt(&config, &Vigenere, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Vigenere), Mode::Plaintext) => {
+            run_keyword_plaintext(&config, |c, p, _| attack::vigenere_plaintext(c, p))?;
+        }
+        (Some(Cipher::Vigenere), Mode::Ciphertext) => {
+            run_vigenere_ciphertext(&config, &read_file(&config.files.crypto)?)?;
+        }
+        (Some(Cipher::Hill), Mode::Encrypt) => {
+            run_encrypt(&config, &Hill, &key_matrix(&config)?)?;
+        }
+        (Some(Cipher::Hill), Mode::Decrypt) => {
+            run_decrypt(&config, &Hill, &key_matrix(&config)?)?;
+        }
+        (Some(Cipher::Hill), Mode::Plaintext) => {
+            run_hill_plaintext(&config)?;
+        }
+        (Some(Cipher::Hill), Mode::Ciphertext) => {
+            return Err(CipherError::Unsupported(
+                "Ciphertext-only cryptanalysis is not supported for the Hill cipher.".into(),
+            )
+            .into());
+        }
+        (Some(Cipher::Monoalphabetic), Mode::Encrypt) => {
+            run_encrypt(&config, &Monoalphabetic, &key_permutation(&config)?)?;
+        }
+        (Some(Cipher::Monoalphabetic), Mode::Decrypt) => {
+            run_decrypt(&config, &Monoalphabetic, &key_permutation(&config)?)?;
+        }
+        (Some(Cipher::Monoalphabetic), Mode::Plaintext) => {
+            return Err(CipherError::Unsupported(
+                "Known-plaintext cryptanalysis is not supported for the monoalphabetic cipher."
+                    .into(),
+            )
+            .into());
+        }
+        (Some(Cipher::Monoalphabetic), Mode::Ciphertext) => {
+            run_monoalphabetic_ciphertext(&config, &read_file(&config.files.crypto)?)?;
+        }
+        (Some(Cipher::RailFence), Mode::Encrypt) => {
+            run_encrypt(&config, &RailFence, &key_rails(&config)?)?;
+        }
+        (Some(Cipher::RailFence), Mode::Decrypt) => {
+            run_decrypt(&config, &RailFence, &key_rails(&config)?)?;
+        }
+        (Some(Cipher::RailFence), Mode::Ciphertext) => {
+            run_rail_fence_ciphertext(&config, &read_file(&config.files.crypto)?)?;
+        }
+        (Some(Cipher::Columnar), Mode::Encrypt) => {
+            run_encrypt(&config, &Columnar, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Columnar), Mode::Decrypt) => {
+            run_decrypt(&config, &Columnar, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Columnar), Mode::Ciphertext) => {
+            run_columnar_ciphertext(&config, &read_file(&config.files.crypto)?)?;
+        }
+        (Some(Cipher::DoubleColumnar), Mode::Encrypt) => {
+            run_encrypt(&config, &DoubleColumnar, &key_words(&config)?)?;
+        }
+        (Some(Cipher::DoubleColumnar), Mode::Decrypt) => {
+            run_decrypt(&config, &DoubleColumnar, &key_words(&config)?)?;
+        }
+        (Some(Cipher::RailFence | Cipher::Columnar | Cipher::DoubleColumnar), Mode::Plaintext) => {
+            return Err(CipherError::Unsupported(
+                "Known-plaintext cryptanalysis is not supported for transposition ciphers.".into(),
+            )
+            .into());
+        }
+        (Some(Cipher::DoubleColumnar), Mode::Ciphertext) => {
+            return Err(CipherError::Unsupported(
+                "Ciphertext-only cryptanalysis is not supported for the double columnar transposition."
+                    .into(),
+            )
+            .into());
+        }
+        (Some(Cipher::Playfair), Mode::Encrypt) => {
+            run_encrypt(&config, &Playfair, &key_square(&config)?)?;
+        }
+        (Some(Cipher::Playfair), Mode::Decrypt) => {
+            run_decrypt(&config, &Playfair, &key_square(&config)?)?;
+        }
+        (Some(Cipher::Playfair), Mode::Plaintext) => {
+            return Err(CipherError::Unsupported(
+                "Known-plaintext cryptanalysis is not supported for the Playfair cipher.".into(),
+            )
+            .into());
+        }
+        (Some(Cipher::Playfair), Mode::Ciphertext) => {
+            run_playfair_ciphertext(&config)?;
+        }
+        (Some(Cipher::Autokey), Mode::Encrypt) => {
+            run_encrypt(&config, &autokey, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Autokey), Mode::Decrypt) => {
+            run_decrypt(&config, &autokey, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Autokey), Mode::Plaintext) => {
+            run_keyword_plaintext(&config, attack::autokey_plaintext)?;
+        }
+        (Some(Cipher::Beaufort), Mode::Encrypt) => {
+            run_encrypt(&config, &beaufort, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Beaufort), Mode::Decrypt) => {
+            run_decrypt(&config, &beaufort, &key_word(&config)?)?;
+        }
+        (Some(Cipher::Beaufort), Mode::Plaintext) => {
+            run_keyword_plaintext(&config, attack::beaufort_plaintext)?;
+        }
+        (Some(Cipher::VariantBeaufort), Mode::Encrypt) => {
+            run_encrypt(&config, &variant_beaufort, &key_word(&config)?)?;
+        }
+        (Some(Cipher::VariantBeaufort), Mode::Decrypt) => {
+            run_decrypt(&config, &variant_beaufort, &key_word(&config)?)?;
+        }
+        (Some(Cipher::VariantBeaufort), Mode::Plaintext) => {
+            run_keyword_plaintext(&config, attack::variant_beaufort_plaintext)?;
+        }
+        (Some(Cipher::Autokey | Cipher::Beaufort | Cipher::VariantBeaufort), Mode::Ciphertext) => {
+            return Err(CipherError::Unsupported(
+                "Ciphertext-only cryptanalysis is not supported for the autokey and Beaufort ciphers."
+                    .into(),
+            )
+            .into());
+        }
+        (None, _) | (_, Mode::Stats | Mode::Identify | Mode::Train(_)) => {
+            unreachable!("the modes without a cipher are handled before choosing one")
         }
     }
 
     Ok(())
 }
+
+/// Errors go to stderr as `error: <kind>: <message>` and set the exit code
+/// of their kind, see `CipherError::exit_code`.
+fn main() -> ExitCode {
+    match run(cli::Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => match err.downcast_ref::<CipherError>() {
+            Some(e) => {
+                eprintln!("error: {}: {}", e.kind(), e);
+                ExitCode::from(e.exit_code())
+            }
+            None => {
+                eprintln!("error: other: {}", err);
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
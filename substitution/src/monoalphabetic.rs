@@ -0,0 +1,114 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+use rand::seq::SliceRandom;
+
+use crate::{Cipher, CipherError};
+
+/// Positions (0-25) of the cipher letters for a-z, validated to be a permutation.
+fn key_positions(key: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let positions: Vec<u8> = key
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect();
+
+    let mut seen = [false; 26];
+    for &p in &positions {
+        seen[p as usize] = true;
+    }
+    if positions.len() != 26 || seen.iter().any(|&s| !s) {
+        return Err(CipherError::InvalidKey(
+            "Invalid key (permutation of the 26 letters needed)".into(),
+        )
+        .into());
+    }
+
+    Ok(positions)
+}
+
+/// Random permutation of the alphabet.
+pub fn random_key() -> String {
+    let mut letters: Vec<u8> = (b'a'..=b'z').collect();
+    letters.shuffle(&mut rand::thread_rng());
+    String::from_utf8(letters).unwrap()
+}
+
+/// Keyword without repeated letters followed by the remaining letters of
+/// the alphabet, e.g. "krypto" -> "kryptoabcdefghijlmnqsuvwxz".
+pub fn keyword_key(keyword: &str) -> Result<String, Box<dyn Error>> {
+    if !keyword.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(CipherError::InvalidKey("Invalid keyword (ASCII letters only)".into()).into());
+    }
+
+    let mut key = String::new();
+    for c in keyword
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .chain('a'..='z')
+    {
+        if !key.contains(c) {
+            key.push(c);
+        }
+    }
+    Ok(key)
+}
+
+/// Accepts a full 26-letter permutation or a shorter keyword to derive one
+/// from. 26 letters or more must be a permutation, so that a typo in one is
+/// not taken for a keyword.
+pub fn parse_key(key: &str) -> Result<String, Box<dyn Error>> {
+    if key.chars().count() < 26 {
+        return keyword_key(key);
+    }
+    let positions = key_positions(key)?;
+    Ok(positions.iter().map(|&p| (p + b'a') as char).collect())
+}
+
+fn substitute(text: &str, table: &[u8]) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                let base = if c.is_uppercase() { b'A' } else { b'a' };
+                (table[(c as u8 - base) as usize] + base) as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Inverse permutation, mapping cipher letters back to plain letters.
+pub fn invert(positions: &[u8]) -> Vec<u8> {
+    let mut inverse = vec![0; positions.len()];
+    for (plain, &cipher) in positions.iter().enumerate() {
+        inverse[cipher as usize] = plain as u8;
+    }
+    inverse
+}
+
+/// `key[i]` is the ciphertext letter for the i-th letter of the alphabet.
+pub fn encrypt(plaintext: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    Ok(substitute(plaintext, &key_positions(key)?))
+}
+
+pub fn decrypt(ciphertext: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    Ok(substitute(ciphertext, &invert(&key_positions(key)?)))
+}
+
+/// General monoalphabetic substitution keyed with a permutation of a-z.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Monoalphabetic;
+
+impl Cipher for Monoalphabetic {
+    type Key = String;
+
+    fn encrypt(&self, plaintext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        encrypt(plaintext, key)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        decrypt(ciphertext, key)
+    }
+}
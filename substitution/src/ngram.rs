@@ -0,0 +1,122 @@
+// 2024 Paweł Rybak
+
+use std::{collections::HashMap, error::Error};
+
+use crate::CipherError;
+
+/// N-gram log-probability table used to measure how much a text resembles
+/// the language it was built from.
+#[derive(Debug, Clone)]
+pub struct Ngrams {
+    n: usize,
+    floor: f64,
+    log_probs: Vec<f64>,
+}
+
+fn index(ngram: &[u8]) -> usize {
+    ngram.iter().fold(0, |acc, &l| acc * 26 + l as usize)
+}
+
+fn ngram_at(index: usize, n: usize) -> Vec<u8> {
+    (0..n)
+        .rev()
+        .map(|i| (index / 26usize.pow(i as u32) % 26) as u8)
+        .collect()
+}
+
+/// Letters (0-25) of an n-gram written in ASCII letters.
+pub fn parse_ngram(ngram: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if ngram.is_empty() || !ngram.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(CipherError::InvalidInput(format!("Invalid n-gram: '{}'", ngram)).into());
+    }
+    Ok(ngram
+        .bytes()
+        .map(|b| b.to_ascii_lowercase() - b'a')
+        .collect())
+}
+
+impl Ngrams {
+    /// N-grams missing from the counts get a floor probability a bit lower
+    /// than a single occurrence.
+    pub fn from_counts(n: usize, counts: &HashMap<Vec<u8>, usize>) -> Self {
+        let total = counts.values().sum::<usize>().max(1) as f64;
+        let floor = (0.01 / total).log10();
+        let mut log_probs = vec![floor; 26usize.pow(n as u32)];
+        for (ngram, &count) in counts {
+            log_probs[index(ngram)] = (count as f64 / total).log10();
+        }
+
+        Ngrams {
+            n,
+            floor,
+            log_probs,
+        }
+    }
+
+    /// Counts the runs of `n` consecutive letters, dropping the ones seen
+    /// fewer than `min_count` times.
+    pub fn train(letters: &[u8], n: usize, min_count: usize) -> Self {
+        let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+        for ngram in letters.windows(n) {
+            *counts.entry(ngram.to_vec()).or_default() += 1;
+        }
+        counts.retain(|_, &mut c| c >= min_count);
+        Self::from_counts(n, &counts)
+    }
+
+    /// Table of the given log10 probabilities, `floor` for the rest.
+    pub fn from_log_probs(
+        n: usize,
+        floor: f64,
+        entries: &[(Vec<u8>, f64)],
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut log_probs = vec![floor; 26usize.pow(n as u32)];
+        for (ngram, log_prob) in entries {
+            if ngram.len() != n {
+                return Err(CipherError::InvalidInput(format!(
+                    "N-gram of length {} in a table of {}-grams",
+                    ngram.len(),
+                    n
+                ))
+                .into());
+            }
+            log_probs[index(ngram)] = *log_prob;
+        }
+
+        Ok(Ngrams {
+            n,
+            floor,
+            log_probs,
+        })
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn floor(&self) -> f64 {
+        self.floor
+    }
+
+    pub fn log_prob(&self, ngram: &[u8]) -> f64 {
+        self.log_probs[index(ngram)]
+    }
+
+    /// N-grams more probable than the floor with their log10 probabilities.
+    pub fn entries(&self) -> Vec<(Vec<u8>, f64)> {
+        self.log_probs
+            .iter()
+            .enumerate()
+            .filter(|&(_, &p)| p > self.floor)
+            .map(|(i, &p)| (ngram_at(i, self.n), p))
+            .collect()
+    }
+
+    /// Sum of n-gram log-probabilities of the letters (0-25). Higher is better.
+    pub fn fitness(&self, letters: &[u8]) -> f64 {
+        letters
+            .windows(self.n)
+            .map(|ngram| self.log_probs[index(ngram)])
+            .sum()
+    }
+}
@@ -0,0 +1,202 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+use crate::{stats, Cipher, CipherError};
+
+/// Letter inserted between doubled letters and after an odd final letter.
+pub const FILLER: u8 = b'x' - b'a';
+
+/// Filler used when the doubled letter is the filler itself.
+pub const ALT_FILLER: u8 = b'q' - b'a';
+
+/// 5x5 key square. One letter of the alphabet is left out and replaced by
+/// another one, by default 'j' is written as 'i'.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Square {
+    letters: Vec<u8>,
+    positions: [usize; 26],
+    merge: (u8, u8),
+}
+
+impl Square {
+    /// Builds the square from a sequence of letters (0-25). `merge` is the
+    /// `(from, to)` pair of merged letters; `from` must not appear in `letters`.
+    pub fn new(letters: Vec<u8>, merge: (u8, u8)) -> Result<Self, Box<dyn Error>> {
+        let mut positions = [usize::MAX; 26];
+        for (i, &l) in letters.iter().enumerate() {
+            if l as usize >= 26 || l == merge.0 || positions[l as usize] != usize::MAX {
+                return Err(CipherError::InvalidKey(
+                    "Invalid key square (25 distinct letters needed)".into(),
+                )
+                .into());
+            }
+            positions[l as usize] = i;
+        }
+        if letters.len() != 25 {
+            return Err(CipherError::InvalidKey(
+                "Invalid key square (25 distinct letters needed)".into(),
+            )
+            .into());
+        }
+        positions[merge.0 as usize] = positions[merge.1 as usize];
+
+        Ok(Square {
+            letters,
+            positions,
+            merge,
+        })
+    }
+
+    /// Keyword without repeated letters followed by the rest of the alphabet.
+    pub fn from_keyword(keyword: &str, merge: (char, char)) -> Result<Self, Box<dyn Error>> {
+        let merge = parse_merge(merge)?;
+        if !keyword.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(
+                CipherError::InvalidKey("Invalid keyword (ASCII letters only)".into()).into(),
+            );
+        }
+
+        let mut letters = Vec::new();
+        for l in stats::letters(keyword).into_iter().chain(0..26) {
+            let l = if l == merge.0 { merge.1 } else { l };
+            if !letters.contains(&l) {
+                letters.push(l);
+            }
+        }
+        Self::new(letters, merge)
+    }
+
+    /// Reads a square written as 25 letters (whitespace ignored).
+    pub fn from_letters(square: &str, merge: (char, char)) -> Result<Self, Box<dyn Error>> {
+        Self::new(stats::letters(square), parse_merge(merge)?)
+    }
+
+    pub fn letters(&self) -> &[u8] {
+        &self.letters
+    }
+
+    pub fn merge(&self) -> (u8, u8) {
+        self.merge
+    }
+
+    fn at(&self, row: usize, col: usize) -> u8 {
+        self.letters[(row % 5) * 5 + col % 5]
+    }
+
+    /// Encrypts (`shift` = 1) or decrypts (`shift` = 4) a digraph.
+    fn digraph(&self, a: u8, b: u8, shift: usize) -> (u8, u8) {
+        let (pa, pb) = (self.positions[a as usize], self.positions[b as usize]);
+        let (ra, ca, rb, cb) = (pa / 5, pa % 5, pb / 5, pb % 5);
+        if ra == rb {
+            (self.at(ra, ca + shift), self.at(rb, cb + shift))
+        } else if ca == cb {
+            (self.at(ra + shift, ca), self.at(rb + shift, cb))
+        } else {
+            (self.at(ra, cb), self.at(rb, ca))
+        }
+    }
+
+    /// Applies the square to letters already split into digraphs.
+    pub fn transform(&self, letters: &[u8], encrypt: bool) -> Vec<u8> {
+        let shift = if encrypt { 1 } else { 4 };
+        letters
+            .chunks(2)
+            .flat_map(|pair| {
+                let (a, b) = self.digraph(pair[0], pair[1], shift);
+                [a, b]
+            })
+            .collect()
+    }
+
+    /// Square as five rows of letters.
+    pub fn to_rows(&self) -> String {
+        self.letters
+            .chunks(5)
+            .map(|row| row.iter().map(|&l| (l + b'a') as char).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn parse_merge(merge: (char, char)) -> Result<(u8, u8), Box<dyn Error>> {
+    let (from, to) = (merge.0.to_ascii_lowercase(), merge.1.to_ascii_lowercase());
+    if !from.is_ascii_lowercase() || !to.is_ascii_lowercase() || from == to {
+        return Err(CipherError::InvalidKey(
+            "Invalid letter merge (two different letters needed)".into(),
+        )
+        .into());
+    }
+    Ok((from as u8 - b'a', to as u8 - b'a'))
+}
+
+/// Splits the letters of the text into digraphs: merged letters are
+/// replaced, doubled letters in a digraph are separated with a filler and
+/// an odd final letter gets one too.
+pub fn digraphs(text: &str, merge: (u8, u8)) -> Vec<u8> {
+    let letters: Vec<u8> = stats::letters(text)
+        .into_iter()
+        .map(|l| if l == merge.0 { merge.1 } else { l })
+        .collect();
+
+    let mut prepared = Vec::with_capacity(letters.len() + 1);
+    let mut i = 0;
+    while i < letters.len() {
+        let a = letters[i];
+        let filler = if a == FILLER { ALT_FILLER } else { FILLER };
+        match letters.get(i + 1) {
+            Some(&b) if b != a => {
+                prepared.extend([a, b]);
+                i += 2;
+            }
+            _ => {
+                prepared.extend([a, filler]);
+                i += 1;
+            }
+        }
+    }
+    prepared
+}
+
+fn to_text(letters: &[u8]) -> String {
+    letters.iter().map(|&l| (l + b'a') as char).collect()
+}
+
+/// Only the letters are encrypted, the result is a plain run of letters.
+pub fn encrypt(plaintext: &str, key: &Square) -> Result<String, Box<dyn Error>> {
+    Ok(to_text(
+        &key.transform(&digraphs(plaintext, key.merge), true),
+    ))
+}
+
+/// The fillers stay in the decrypted text.
+pub fn decrypt(ciphertext: &str, key: &Square) -> Result<String, Box<dyn Error>> {
+    let letters = stats::letters(ciphertext);
+    if !letters.len().is_multiple_of(2) {
+        return Err(
+            CipherError::InvalidInput("Ciphertext has an odd number of letters.".into()).into(),
+        );
+    }
+    if letters.contains(&key.merge.0) {
+        return Err(
+            CipherError::InvalidInput("Ciphertext contains the merged letter.".into()).into(),
+        );
+    }
+    Ok(to_text(&key.transform(&letters, false)))
+}
+
+/// Playfair cipher keyed with a 5x5 square.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Playfair;
+
+impl Cipher for Playfair {
+    type Key = Square;
+
+    fn encrypt(&self, plaintext: &str, key: &Square) -> Result<String, Box<dyn Error>> {
+        encrypt(plaintext, key)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &Square) -> Result<String, Box<dyn Error>> {
+        decrypt(ciphertext, key)
+    }
+}
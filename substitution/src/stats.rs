@@ -0,0 +1,108 @@
+// 2024 Paweł Rybak
+
+use std::collections::HashMap;
+
+/// Index of coincidence of uniformly random letters (1 / 26).
+pub const RANDOM_IOC: f64 = 0.0385;
+
+/// Positions (0-25) of the ASCII letters in `text`, case-insensitive.
+pub fn letters(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect()
+}
+
+pub fn letter_counts(letters: &[u8]) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for &l in letters {
+        counts[l as usize] += 1;
+    }
+    counts
+}
+
+/// https://en.wikipedia.org/wiki/Index_of_coincidence
+pub fn index_of_coincidence(letters: &[u8]) -> f64 {
+    let n = letters.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let sum: usize = letter_counts(letters)
+        .iter()
+        .map(|&c| c * c.saturating_sub(1))
+        .sum();
+    sum as f64 / (n * (n - 1)) as f64
+}
+
+/// Shannon entropy of the distribution given by `counts`, in bits per symbol.
+/// https://en.wikipedia.org/wiki/Entropy_(information_theory)
+pub fn entropy(counts: &[usize]) -> f64 {
+    let n: usize = counts.iter().sum();
+    if n == 0 {
+        return 0.0;
+    }
+
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / n as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// The `count` most frequent runs of `n` consecutive letters, most frequent
+/// first (ties in alphabetical order).
+pub fn top_ngrams(letters: &[u8], n: usize, count: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&[u8], usize> = HashMap::new();
+    for ngram in letters.windows(n) {
+        *counts.entry(ngram).or_default() += 1;
+    }
+
+    let mut ngrams: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(ngram, c)| (ngram.iter().map(|&l| (l + b'a') as char).collect(), c))
+        .collect();
+    ngrams.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ngrams.truncate(count);
+    ngrams
+}
+
+/// Chi-squared of the letter frequencies sorted by size against the sorted
+/// `expected` ones. A monoalphabetic substitution only permutes the letters,
+/// so it keeps this low while polyalphabetic ciphers flatten the profile.
+pub fn sorted_chi_squared(letters: &[u8], expected: &[f64; 26]) -> f64 {
+    let n = letters.len() as f64;
+    let mut observed = letter_counts(letters);
+    observed.sort_unstable_by(|a, b| b.cmp(a));
+    let mut expected = *expected;
+    expected.sort_unstable_by(|a, b| b.total_cmp(a));
+
+    observed
+        .iter()
+        .zip(expected.iter())
+        .map(|(&o, &freq)| (o as f64 - freq * n).powi(2) / (freq * n))
+        .sum()
+}
+
+/// Pearson's chi-squared statistic of the letter counts against the
+/// `expected` frequencies (a-z). Lower is closer.
+pub fn chi_squared(letters: &[u8], expected: &[f64; 26]) -> f64 {
+    let n = letters.len() as f64;
+    letter_counts(letters)
+        .iter()
+        .zip(expected.iter())
+        .map(|(&observed, &freq)| {
+            let expected = freq * n;
+            (observed as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Log-likelihood of the letters under the unigram model given by the
+/// `expected` frequencies. Higher is closer.
+pub fn log_likelihood(letters: &[u8], expected: &[f64; 26]) -> f64 {
+    letters.iter().map(|&l| expected[l as usize].ln()).sum()
+}
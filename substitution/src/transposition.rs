@@ -0,0 +1,169 @@
+// 2024 Paweł Rybak
+
+use std::error::Error;
+
+use crate::{Cipher, CipherError};
+
+/// Rail of every position of a text of `len` characters written in a zigzag.
+fn rail_pattern(len: usize, rails: usize) -> Vec<usize> {
+    let cycle = 2 * (rails - 1);
+    (0..len)
+        .map(|i| {
+            let pos = i % cycle;
+            if pos < rails {
+                pos
+            } else {
+                cycle - pos
+            }
+        })
+        .collect()
+}
+
+/// Order in which the positions of the text are read off the fence.
+fn rail_order(len: usize, rails: usize) -> Vec<usize> {
+    let pattern = rail_pattern(len, rails);
+    let mut order: Vec<usize> = (0..len).collect();
+    order.sort_by_key(|&i| pattern[i]);
+    order
+}
+
+fn check_rails(rails: usize) -> Result<(), Box<dyn Error>> {
+    if rails < 2 {
+        return Err(CipherError::InvalidKey("Invalid key (at least 2 rails needed)".into()).into());
+    }
+    Ok(())
+}
+
+pub fn rail_fence_encrypt(plaintext: &str, rails: usize) -> Result<String, Box<dyn Error>> {
+    check_rails(rails)?;
+    let chars: Vec<char> = plaintext.chars().collect();
+    Ok(rail_order(chars.len(), rails)
+        .into_iter()
+        .map(|i| chars[i])
+        .collect())
+}
+
+pub fn rail_fence_decrypt(ciphertext: &str, rails: usize) -> Result<String, Box<dyn Error>> {
+    check_rails(rails)?;
+    let chars: Vec<char> = ciphertext.chars().collect();
+    let mut plain = vec![' '; chars.len()];
+    for (&c, i) in chars.iter().zip(rail_order(chars.len(), rails)) {
+        plain[i] = c;
+    }
+    Ok(plain.into_iter().collect())
+}
+
+/// Rank of every column: columns are read in the alphabetical order of the
+/// keyword letters, repeated letters from left to right.
+pub fn column_order(keyword: &str) -> Result<Vec<usize>, Box<dyn Error>> {
+    if keyword.chars().count() < 2 || !keyword.chars().all(|c| c.is_alphanumeric()) {
+        return Err(CipherError::InvalidKey(
+            "Invalid key (keyword of at least 2 letters needed)".into(),
+        )
+        .into());
+    }
+
+    let letters: Vec<char> = keyword.chars().flat_map(|c| c.to_lowercase()).collect();
+    let mut columns: Vec<usize> = (0..letters.len()).collect();
+    columns.sort_by_key(|&i| letters[i]);
+    let mut order = vec![0; letters.len()];
+    for (rank, col) in columns.into_iter().enumerate() {
+        order[col] = rank;
+    }
+    Ok(order)
+}
+
+/// Keyword reproducing the given column order, e.g. [2, 0, 1] -> "cab".
+pub fn order_keyword(order: &[usize]) -> String {
+    order.iter().map(|&r| (b'a' + r as u8) as char).collect()
+}
+
+/// Writes the text row by row under the key and reads it column by column
+/// in the order of the ranks. The last row may be incomplete.
+pub fn columnar_transpose(chars: &[char], order: &[usize]) -> Vec<char> {
+    let n = order.len();
+    let mut columns: Vec<usize> = (0..n).collect();
+    columns.sort_by_key(|&c| order[c]);
+    columns
+        .into_iter()
+        .flat_map(|c| chars.iter().skip(c).step_by(n).copied())
+        .collect()
+}
+
+pub fn columnar_untranspose(chars: &[char], order: &[usize]) -> Vec<char> {
+    let n = order.len();
+    let (rows, full_columns) = (chars.len() / n, chars.len() % n);
+    let mut columns: Vec<usize> = (0..n).collect();
+    columns.sort_by_key(|&c| order[c]);
+
+    let mut plain = vec![' '; chars.len()];
+    let mut start = 0;
+    for c in columns {
+        let len = rows + usize::from(c < full_columns);
+        for (row, &ch) in chars[start..start + len].iter().enumerate() {
+            plain[row * n + c] = ch;
+        }
+        start += len;
+    }
+    plain
+}
+
+pub fn columnar_encrypt(plaintext: &str, keyword: &str) -> Result<String, Box<dyn Error>> {
+    let order = column_order(keyword)?;
+    let chars: Vec<char> = plaintext.chars().collect();
+    Ok(columnar_transpose(&chars, &order).into_iter().collect())
+}
+
+pub fn columnar_decrypt(ciphertext: &str, keyword: &str) -> Result<String, Box<dyn Error>> {
+    let order = column_order(keyword)?;
+    let chars: Vec<char> = ciphertext.chars().collect();
+    Ok(columnar_untranspose(&chars, &order).into_iter().collect())
+}
+
+/// Rail fence cipher keyed with the number of rails.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RailFence;
+
+impl Cipher for RailFence {
+    type Key = usize;
+
+    fn encrypt(&self, plaintext: &str, key: &usize) -> Result<String, Box<dyn Error>> {
+        rail_fence_encrypt(plaintext, *key)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &usize) -> Result<String, Box<dyn Error>> {
+        rail_fence_decrypt(ciphertext, *key)
+    }
+}
+
+/// Keyed columnar transposition.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Columnar;
+
+impl Cipher for Columnar {
+    type Key = String;
+
+    fn encrypt(&self, plaintext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        columnar_encrypt(plaintext, key)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        columnar_decrypt(ciphertext, key)
+    }
+}
+
+/// Columnar transposition applied twice, keyed with two keywords.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoubleColumnar;
+
+impl Cipher for DoubleColumnar {
+    type Key = (String, String);
+
+    fn encrypt(&self, plaintext: &str, key: &(String, String)) -> Result<String, Box<dyn Error>> {
+        columnar_encrypt(&columnar_encrypt(plaintext, &key.0)?, &key.1)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &(String, String)) -> Result<String, Box<dyn Error>> {
+        columnar_decrypt(&columnar_decrypt(ciphertext, &key.1)?, &key.0)
+    }
+}
@@ -0,0 +1,149 @@
+// 2024 Paweł Rybak
+
+use std::{collections::HashMap, error::Error};
+
+use crate::{stats, Cipher, CipherError};
+
+fn key_shifts(key: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(CipherError::InvalidKey(
+            "Invalid key (key must be a non-empty word of ASCII letters)".into(),
+        )
+        .into());
+    }
+
+    Ok(key.bytes().map(|k| k.to_ascii_lowercase() - b'a').collect())
+}
+
+fn shift_text(text: &str, shifts: &[u8]) -> String {
+    let mut i = 0;
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                let base = if c.is_uppercase() { b'A' } else { b'a' };
+                let shift = shifts[i % shifts.len()];
+                i += 1;
+                (((c as u8 - base + shift) % 26) + base) as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+pub fn encrypt(plaintext: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    let shifts = key_shifts(key)?;
+    Ok(shift_text(plaintext, &shifts))
+}
+
+pub fn decrypt(ciphertext: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    let shifts: Vec<u8> = key_shifts(key)?
+        .into_iter()
+        .map(|s| (26 - s) % 26)
+        .collect();
+    Ok(shift_text(ciphertext, &shifts))
+}
+
+/// Vigenère cipher keyed with a word.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Vigenere;
+
+impl Cipher for Vigenere {
+    type Key = String;
+
+    fn encrypt(&self, plaintext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        encrypt(plaintext, key)
+    }
+
+    fn decrypt(&self, ciphertext: &str, key: &String) -> Result<String, Box<dyn Error>> {
+        decrypt(ciphertext, key)
+    }
+}
+
+/// Longest key period worth testing for a text of `len` letters.
+pub fn max_period(len: usize) -> usize {
+    const MAX_PERIOD: usize = 20;
+    const MIN_COSET_LEN: usize = 10;
+    MAX_PERIOD.min(len / MIN_COSET_LEN).max(1)
+}
+
+/// Kasiski examination: distances between repeated trigrams are multiples of
+/// the key length, so every factor of a distance gets a vote.
+/// Returns `(period, votes)` pairs sorted by the number of votes.
+pub fn kasiski(letters: &[u8], max_period: usize) -> Vec<(usize, usize)> {
+    let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+    let mut votes = vec![0; max_period + 1];
+
+    for (i, trigram) in letters.windows(3).enumerate() {
+        if let Some(prev) = last_seen.insert(trigram, i) {
+            let distance = i - prev;
+            for (period, vote) in votes.iter_mut().enumerate().skip(2) {
+                if distance % period == 0 {
+                    *vote += 1;
+                }
+            }
+        }
+    }
+
+    let mut ranking: Vec<(usize, usize)> = votes
+        .into_iter()
+        .enumerate()
+        .skip(2)
+        .filter(|&(_, v)| v > 0)
+        .collect();
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranking
+}
+
+/// Key length estimate for a plaintext language with index of coincidence
+/// `language_ioc`.
+/// https://en.wikipedia.org/wiki/Vigen%C3%A8re_cipher#Friedman_test
+pub fn friedman(letters: &[u8], language_ioc: f64) -> f64 {
+    let n = letters.len() as f64;
+    let ioc = stats::index_of_coincidence(letters);
+    let denominator = (n - 1.0) * ioc - stats::RANDOM_IOC * n + language_ioc;
+    if denominator <= 0.0 {
+        return n;
+    }
+
+    (language_ioc - stats::RANDOM_IOC) * n / denominator
+}
+
+/// Splits the letters into `period` cosets, each encrypted with one key letter.
+pub fn cosets(letters: &[u8], period: usize) -> Vec<Vec<u8>> {
+    let mut cosets = vec![Vec::new(); period];
+    for (i, &l) in letters.iter().enumerate() {
+        cosets[i % period].push(l);
+    }
+    cosets
+}
+
+/// Average index of coincidence of the cosets for a given period.
+pub fn coset_ioc(letters: &[u8], period: usize) -> f64 {
+    let cosets = cosets(letters, period);
+    cosets
+        .iter()
+        .map(|c| stats::index_of_coincidence(c))
+        .sum::<f64>()
+        / period as f64
+}
+
+/// Sum of the best Caesar shift log-likelihood of every coset of the given
+/// period, `log_probs` being the log10 letter probabilities.
+pub fn periodic_fitness(letters: &[u8], period: usize, log_probs: &[f64; 26]) -> f64 {
+    cosets(letters, period)
+        .iter()
+        .map(|coset| {
+            let counts = stats::letter_counts(coset);
+            (0..26)
+                .map(|k| {
+                    counts
+                        .iter()
+                        .enumerate()
+                        .map(|(c, &n)| n as f64 * log_probs[(c + 26 - k) % 26])
+                        .sum::<f64>()
+                })
+                .fold(f64::MIN, f64::max)
+        })
+        .sum()
+}
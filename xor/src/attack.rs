@@ -0,0 +1,79 @@
+// 2024 Paweł Rybak
+
+use substitution::Language;
+
+/// Share of spaces in the prepared plaintext, the rest are letters.
+const SPACE_SHARE: f64 = 0.18;
+
+/// Stands for the key bytes and plaintext characters that could not be recovered.
+pub const UNKNOWN: u8 = b'_';
+
+/// The prepared plaintext only has lowercase letters and spaces.
+fn is_plain(b: u8) -> bool {
+    b.is_ascii_lowercase() || b == b' '
+}
+
+/// Splits the ciphertext into lines of `key_len` bytes, the way both the
+/// encryption mode and xor.py write them one after another.
+pub fn split_lines(data: &[u8], key_len: usize) -> Vec<Vec<u8>> {
+    data.chunks(key_len).map(|line| line.to_vec()).collect()
+}
+
+/// Log10 probabilities of the plaintext bytes: spaces and the letters of `language`.
+pub fn log_probs(language: &Language) -> [f64; 256] {
+    let mut log_probs = [f64::NEG_INFINITY; 256];
+    log_probs[b' ' as usize] = SPACE_SHARE.log10();
+    for (l, f) in language.frequencies().iter().enumerate() {
+        log_probs[b'a' as usize + l] = ((1.0 - SPACE_SHARE) * f).log10();
+    }
+    log_probs
+}
+
+/// Key byte of one column of the lines. The XOR of two ciphertext bytes is
+/// the XOR of their plaintext bytes, and a space XOR a letter is above 0x40
+/// while two letters give less than 0x20. So every byte that gives a large
+/// XOR with another one may be an encrypted space, and its XOR with a space
+/// a key byte. Of the keys that decrypt the whole column into letters and
+/// spaces the most probable decryption wins, which matters when the column
+/// has only two distinct characters, e.g. spaces and 'n'.
+pub fn column_key(column: &[u8], log_probs: &[f64; 256]) -> Option<u8> {
+    let mut keys: Vec<u8> = column
+        .iter()
+        .filter(|&&c| column.iter().any(|&other| c ^ other >= 0x40))
+        .map(|&c| c ^ b' ')
+        .filter(|&key| column.iter().all(|&c| is_plain(c ^ key)))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    let fitness = |key: u8| -> f64 { column.iter().map(|&c| log_probs[(c ^ key) as usize]).sum() };
+    keys.into_iter()
+        .max_by(|&a, &b| fitness(a).total_cmp(&fitness(b)))
+}
+
+/// Key bytes recovered from every column, `None` where no line has a space.
+pub fn recover_key(lines: &[Vec<u8>], key_len: usize, language: &Language) -> Vec<Option<u8>> {
+    let log_probs = log_probs(language);
+    (0..key_len)
+        .map(|i| {
+            let column: Vec<u8> = lines
+                .iter()
+                .filter_map(|line| line.get(i).copied())
+                .collect();
+            column_key(&column, &log_probs)
+        })
+        .collect()
+}
+
+/// Lines decrypted with the known key bytes, `UNKNOWN` in the other columns.
+pub fn decrypt(lines: &[Vec<u8>], key: &[Option<u8>]) -> Vec<Vec<u8>> {
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .zip(key.iter())
+                .map(|(&c, k)| k.map_or(UNKNOWN, |k| c ^ k))
+                .collect()
+        })
+        .collect()
+}
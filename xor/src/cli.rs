@@ -1,14 +1,22 @@
 // 2024 Paweł Rybak
 
-use clap::{ArgGroup, Parser};
+use clap::{ArgGroup, Parser, ValueEnum};
+
+use substitution::bytes;
+
+use crate::format::Format;
 
 #[derive(Debug, Parser)]
 #[command(group(
     ArgGroup::new("mode")
         .required(true)
-        .args(&["prepare", "encrypt", "cryptanalysis"]),
+        .args(&["generate", "prepare", "encrypt", "cryptanalysis", "interactive"]),
 ))]
 pub struct Args {
+    /// Generate a random key of --length bytes into 'key.txt'
+    #[arg(short)]
+    pub generate: bool,
+
     /// Prepare the original plaintext
     #[arg(short)]
     pub prepare: bool,
@@ -20,4 +28,52 @@ pub struct Args {
     /// Ciphertext cryptanalysis
     #[arg(short = 'k')]
     pub cryptanalysis: bool,
+
+    /// Interactive crib dragging on the ciphertext, refining 'key-new.txt'
+    #[arg(short)]
+    pub interactive: bool,
+
+    /// Key length: of the generated key, and of the lines in the preparation and
+    /// cryptanalysis instead of the length of the key
+    #[arg(short = 'n', long, value_name = "N")]
+    pub length: Option<usize>,
+
+    /// Characters of the generated key
+    #[arg(long, value_enum, default_value_t = KeyAlphabet::Printable)]
+    pub key_alphabet: KeyAlphabet,
+
+    /// Format of the key, given inline or in 'key.txt' [default: raw, hex for
+    /// generated keys of any byte]
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub key_format: Option<Format>,
+
+    /// Key given inline instead of reading 'key.txt'
+    #[arg(long, value_name = "KEY")]
+    pub key: Option<String>,
+
+    /// Repeating-key XOR of arbitrary data: encrypt 'plain.txt' as raw bytes with a key
+    /// shorter than the text, or recover the key of 'crypto.txt' without knowing its length
+    #[arg(short = 'x', long)]
+    pub repeating: bool,
+
+    /// Longest key tried by the repeating-key XOR cryptanalysis
+    #[arg(long, value_name = "N", default_value_t = bytes::MAX_KEY_SIZE)]
+    pub max_key_size: usize,
+
+    /// Format of 'crypto.txt', written by the encryption and read by the other modes
+    #[arg(short = 'f', long, value_enum, default_value_t = Format::Raw)]
+    pub format: Format,
+
+    /// Language of the plaintext for the cryptanalysis: 'english', 'polish' or a model
+    /// file trained by the substitution program
+    #[arg(short = 'L', long, value_name = "NAME|FILE", default_value = "english")]
+    pub language: String,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum KeyAlphabet {
+    /// Printable ASCII characters without the space
+    Printable,
+    /// Any byte, for the encryption of arbitrary data
+    Bytes,
 }
@@ -0,0 +1,192 @@
+// 2024 Paweł Rybak
+
+use std::{
+    error::Error,
+    io::{self, BufRead, Write},
+};
+
+use crate::{attack, stream};
+
+const HELP: &str = "\
+Commands:
+  g LINE POS WORD  guess WORD at position POS of line LINE (both from 0) and preview it
+  a                accept the previewed guess
+  u                undo the last accepted guess
+  p                print the lines with the current key
+  s                save the key to 'key-new.txt' and the text to 'decrypt.txt'
+  q                quit
+  h                this help";
+
+/// Crib dragging over lines encrypted with the same key: a guessed word
+/// fixes the key bytes under it, which decrypts the same columns of every
+/// other line.
+struct Session {
+    lines: Vec<Vec<u8>>,
+    key: Vec<Option<u8>>,
+    /// Keys before every accepted guess.
+    history: Vec<Vec<Option<u8>>>,
+    /// Key with the guess being previewed.
+    pending: Option<Vec<Option<u8>>>,
+}
+
+/// Unknown columns as `_`, other unprintable bytes as `?`.
+fn printable(line: &[u8]) -> String {
+    line.iter()
+        .map(|&b| match b {
+            b' '..=b'~' => b as char,
+            _ => '?',
+        })
+        .collect()
+}
+
+impl Session {
+    /// Key with the bytes implied by `word` at `pos` of line `line`.
+    fn guess(&self, line: usize, pos: usize, word: &str) -> Result<Vec<Option<u8>>, String> {
+        let cipher = self
+            .lines
+            .get(line)
+            .ok_or(format!("There are only {} lines.", self.lines.len()))?;
+        let end = match pos.checked_add(word.len()) {
+            Some(end) if !word.is_empty() && end <= cipher.len() => end,
+            _ => {
+                return Err(format!(
+                    "The word must fit in the {} bytes of the line.",
+                    cipher.len()
+                ))
+            }
+        };
+
+        let mut key = self.key.clone();
+        for (i, k) in stream(&cipher[pos..end], word.as_bytes())
+            .into_iter()
+            .enumerate()
+        {
+            key[pos + i] = Some(k);
+        }
+        Ok(key)
+    }
+
+    /// Every line decrypted with `key`, columns that differ from the current
+    /// key marked below.
+    fn print(&self, key: &[Option<u8>]) {
+        let key_bytes: Vec<u8> = key.iter().map(|k| k.unwrap_or(attack::UNKNOWN)).collect();
+        println!("key  |{}|", printable(&key_bytes));
+        for (i, line) in attack::decrypt(&self.lines, key).iter().enumerate() {
+            println!("{:>4} |{}|", i, printable(line));
+        }
+
+        let changes: String = key
+            .iter()
+            .zip(self.key.iter())
+            .map(|(new, old)| match (new, old) {
+                (Some(_), Some(_)) if new != old => '!',
+                (Some(_), None) => '^',
+                _ => ' ',
+            })
+            .collect();
+        if changes.trim().is_empty() {
+            return;
+        }
+        println!("      {}", changes);
+
+        let implied: Vec<String> = key
+            .iter()
+            .zip(self.key.iter())
+            .enumerate()
+            .filter(|(_, (new, old))| new != old)
+            .map(|(i, (new, _))| format!("{}:{:02x}", i, new.unwrap()))
+            .collect();
+        println!("Key bytes: {}", implied.join(" "));
+        if changes.contains('!') {
+            println!("'!' marks known key bytes the guess changes");
+        }
+    }
+}
+
+/// Runs the session on standard input and output. `save` writes the key
+/// and the decrypted lines.
+pub fn run(
+    lines: Vec<Vec<u8>>,
+    key: Vec<Option<u8>>,
+    save: impl Fn(&[Vec<u8>], &[Option<u8>]) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut session = Session {
+        lines,
+        key,
+        history: Vec::new(),
+        pending: None,
+    };
+
+    println!("{}", HELP);
+    println!();
+    session.print(&session.key);
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        if stdin.lock().read_line(&mut input)? == 0 {
+            break;
+        }
+        let input = input.trim_end_matches(['\n', '\r']);
+
+        match input.split_once(' ').unwrap_or((input, "")) {
+            ("g", args) => {
+                let mut parts = args.splitn(3, ' ');
+                let parsed = match (parts.next(), parts.next(), parts.next()) {
+                    (Some(line), Some(pos), Some(word)) => line
+                        .parse()
+                        .ok()
+                        .zip(pos.parse().ok())
+                        .map(|(l, p)| (l, p, word)),
+                    _ => None,
+                };
+                let Some((line, pos, word)) = parsed else {
+                    println!("Usage: g LINE POS WORD");
+                    continue;
+                };
+                match session.guess(line, pos, word) {
+                    Ok(key) => {
+                        session.print(&key);
+                        println!("'a' accepts the guess");
+                        session.pending = Some(key);
+                    }
+                    Err(e) => println!("{}", e),
+                }
+            }
+            ("a", _) => match session.pending.take() {
+                Some(key) => {
+                    let old = std::mem::replace(&mut session.key, key);
+                    session.history.push(old);
+                    let found = session.key.iter().filter(|k| k.is_some()).count();
+                    println!(
+                        "Accepted, {} of {} key bytes known",
+                        found,
+                        session.key.len()
+                    );
+                }
+                None => println!("No guess to accept."),
+            },
+            ("u", _) => match session.history.pop() {
+                Some(key) => {
+                    session.key = key;
+                    session.pending = None;
+                    session.print(&session.key);
+                }
+                None => println!("Nothing to undo."),
+            },
+            ("p", _) => session.print(&session.key),
+            ("s", _) => {
+                save(&session.lines, &session.key)?;
+                println!("Saved");
+            }
+            ("q", _) => break,
+            ("h", _) => println!("{}", HELP),
+            ("", _) => {}
+            _ => println!("Unknown command, 'h' for help."),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// 2024 Paweł Rybak
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+
+/// Encoding of the ciphertext file.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Bytes as they are, lines one after another without separators
+    Raw,
+    /// Every line as hex digits on a line of its own
+    Hex,
+    /// Every line in base64 on a line of its own
+    Base64,
+}
+
+/// Ciphertext lines in the given format. Raw lines are not separated, since
+/// a newline may just as well be an encrypted byte, so the reader splits
+/// them by the key length. The text formats end every line with a newline.
+pub fn encode(lines: &[Vec<u8>], format: Format) -> Vec<u8> {
+    let text: String = match format {
+        Format::Raw => return lines.concat(),
+        Format::Hex => lines.iter().map(|line| hex::encode(line) + "\n").collect(),
+        Format::Base64 => lines
+            .iter()
+            .map(|line| STANDARD.encode(line) + "\n")
+            .collect(),
+    };
+    text.into_bytes()
+}
+
+/// Ciphertext bytes of a file in the given format, the lines of the text
+/// formats joined together.
+pub fn decode(data: &[u8], format: Format) -> Result<Vec<u8>, String> {
+    let name = match format {
+        Format::Raw => return Ok(data.to_vec()),
+        Format::Hex => "hex",
+        Format::Base64 => "base64",
+    };
+
+    let text = std::str::from_utf8(data).map_err(|_| format!("not valid {}", name))?;
+    let mut bytes = Vec::new();
+    for (i, line) in text.lines().map(str::trim).enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let decoded = match format {
+            Format::Raw => unreachable!(),
+            Format::Hex => hex::decode(line).ok(),
+            Format::Base64 => STANDARD.decode(line).ok(),
+        };
+        bytes.extend(decoded.ok_or(format!("line {} is not valid {}", i + 1, name))?);
+    }
+    Ok(bytes)
+}
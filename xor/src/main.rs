@@ -8,21 +8,101 @@ use std::{
     path::Path,
 };
 
-use clap::Parser;
-use cli::Args;
+use clap::{Parser, ValueEnum};
+use cli::{Args, KeyAlphabet};
+use format::Format;
+use rand::{rngs::OsRng, Rng};
+use substitution::{
+    bytes::{self, ByteModel},
+    Language,
+};
 
+mod attack;
 mod cli;
+mod crib;
+mod format;
 
 #[derive(Debug)]
 enum Mode {
+    Generate,
     Prepare,
     Encrypt,
     Cryptanalysis,
+    Interactive,
+    RepeatingEncrypt,
+    RepeatingCryptanalysis,
 }
 
 #[derive(Debug)]
 struct Config(Mode);
 
+/// Random key from the OS random number generator written to 'key.txt'.
+/// Printable keys leave out the space, which editors tend to trim.
+/// Keys of any byte are written in hex unless another text format is given,
+/// since a raw newline at the end would pass for one added by an editor.
+fn generate_key(
+    length: usize,
+    alphabet: KeyAlphabet,
+    format: Option<Format>,
+) -> Result<(), Box<dyn Error>> {
+    let format = match (alphabet, format) {
+        (KeyAlphabet::Bytes, Some(Format::Raw)) => {
+            return Err("Error: Keys of any byte need --key-format hex or base64.".into())
+        }
+        (KeyAlphabet::Bytes, None) => Format::Hex,
+        (_, format) => format.unwrap_or(Format::Raw),
+    };
+    let key: Vec<u8> = (0..length)
+        .map(|_| match alphabet {
+            KeyAlphabet::Printable => OsRng.gen_range(b'!'..=b'~'),
+            KeyAlphabet::Bytes => OsRng.gen(),
+        })
+        .collect();
+
+    File::create("key.txt")?.write_all(&format::encode(&[key], format))?;
+    match format {
+        Format::Raw => println!("Key of {} bytes written to 'key.txt'", length),
+        _ => println!(
+            "Key of {} bytes written to 'key.txt', read it with --key-format {}",
+            length,
+            format.to_possible_value().unwrap().get_name()
+        ),
+    }
+    Ok(())
+}
+
+/// The key given with --key or in 'key.txt', in the --key-format. A raw
+/// key file is meant to be written by hand, so a newline after a printable
+/// key is dropped; keys of any byte are kept in hex or base64.
+fn read_key(args: &Args) -> Result<Vec<u8>, Box<dyn Error>> {
+    let data = match &args.key {
+        Some(key) => key.as_bytes().to_vec(),
+        None => fs::read("key.txt").map_err(|_| "Error: File 'key.txt' not found.")?,
+    };
+    let mut key = format::decode(&data, args.key_format.unwrap_or(Format::Raw))
+        .map_err(|e| format!("Error: Key: {}.", e))?;
+
+    let text_len = key.len() - key.ends_with(b"\n") as usize - key.ends_with(b"\r\n") as usize;
+    let raw_file = args.key.is_none() && matches!(args.key_format, None | Some(Format::Raw));
+    if raw_file && key[..text_len].iter().all(|b| (b' '..=b'~').contains(b)) {
+        key.truncate(text_len);
+    }
+
+    if key.is_empty() {
+        return Err("Error: Key is empty.".into());
+    }
+    Ok(key)
+}
+
+/// The line length given with --length, or else the length of the key.
+fn key_length(args: &Args) -> Result<usize, Box<dyn Error>> {
+    match args.length {
+        Some(0) => Err("Error: Key length must be positive.".into()),
+        Some(length) => Ok(length),
+        None => Ok(read_key(args)?.len()),
+    }
+}
+
 fn prepare_text(chunk_size: usize) -> Result<(), Box<dyn Error>> {
     let text = fs::read_to_string("orig.txt")
         .map_err(|_| "Error: File 'orig.txt' not found.")?
@@ -82,66 +162,165 @@ where
     Ok(chunks)
 }
 
-fn stream(text: &str, key: &str) -> String {
-    text.chars()
-        .zip(key.chars())
-        .map(|(t, k)| (t as u8 ^ k as u8) as char)
-        .collect()
+fn stream(text: &[u8], key: &[u8]) -> Vec<u8> {
+    text.iter().zip(key.iter()).map(|(t, k)| t ^ k).collect()
+}
+
+/// Ciphertext of 'crypto.txt' in the given format.
+fn read_ciphertext(format: Format) -> Result<Vec<u8>, Box<dyn Error>> {
+    let data = fs::read("crypto.txt").map_err(|_| "Error: File 'crypto.txt' not found.")?;
+    format::decode(&data, format).map_err(|e| format!("Error: File 'crypto.txt': {}.", e).into())
 }
 
-fn encrypt() -> Result<(), Box<dyn Error>> {
+fn write_ciphertext(lines: &[Vec<u8>], format: Format) -> io::Result<()> {
+    File::create("crypto.txt")?.write_all(&format::encode(lines, format))
+}
+
+fn encrypt(key: &[u8], format: Format) -> Result<(), Box<dyn Error>> {
     let plain_lines = read_lines("plain.txt").map_err(|_| "Error: File 'plain.txt' not found.")?;
-    println!("{:#?}", plain_lines);
-    let key = fs::read_to_string("key.txt")
-        .map_err(|_| "Error: File 'key.txt' not found.")?
-        .trim()
-        .to_string();
 
-    if key.len() != plain_lines[0].len() {
+    if plain_lines.iter().any(|line| line.len() != key.len()) {
         return Err("Error: Key length must be the same as plain lines length.".into());
     }
 
-    let mut crypto_file = File::create("crypto.txt")?;
-    for line in plain_lines {
-        let encrypted_line = stream(&line, &key);
-        writeln!(crypto_file, "{}", encrypted_line)?;
+    let encrypted_lines: Vec<Vec<u8>> = plain_lines
+        .iter()
+        .map(|line| stream(line.as_bytes(), key))
+        .collect();
+    write_ciphertext(&encrypted_lines, format)?;
+
+    Ok(())
+}
+
+/// Many-time pad cryptanalysis: every line of 'crypto.txt' is encrypted with
+/// the same key, which is recovered column by column.
+fn cryptanalysis(
+    chunk_size: usize,
+    language: &Language,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_ciphertext(format)?;
+    let lines = attack::split_lines(&ciphertext, chunk_size);
+    if lines.len() < 2 {
+        return Err("Error: Ciphertext is too short for the cryptanalysis.".into());
     }
 
+    let key = attack::recover_key(&lines, chunk_size, language);
+    let found = key.iter().filter(|k| k.is_some()).count();
+    println!("Recovered {} of {} key bytes", found, chunk_size);
+
+    save_results(&lines, &key)
+}
+
+fn repeating_encrypt(key: &[u8], format: Format) -> Result<(), Box<dyn Error>> {
+    let plaintext = fs::read("plain.txt").map_err(|_| "Error: File 'plain.txt' not found.")?;
+    write_ciphertext(&[bytes::repeating_xor(&plaintext, key)], format)?;
+    Ok(())
+}
+
+/// Repeating-key XOR cryptanalysis of 'crypto.txt'.
+fn repeating_cryptanalysis(
+    max_key_size: usize,
+    language: &Language,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_ciphertext(format)?;
+
+    print!("Key sizes by Hamming distance:");
+    for (size, distance) in bytes::key_sizes(&ciphertext, max_key_size).iter().take(5) {
+        print!(" {} ({:.3})", size, distance);
+    }
+    println!();
+
+    let res =
+        bytes::repeating_xor_ciphertext(&ciphertext, max_key_size, &ByteModel::text(language))
+            .ok_or("Error: Ciphertext is too short for the cryptanalysis.")?;
+    println!(
+        "Key ({} bytes): {} (hex {})",
+        res.key.len(),
+        String::from_utf8_lossy(&res.key).escape_debug(),
+        hex::encode(&res.key)
+    );
+
+    File::create("decrypt.txt")?.write_all(&bytes::repeating_xor(&ciphertext, &res.key))?;
+    File::create("key-new.txt")?.write_all(&res.key)?;
+
+    Ok(())
+}
+
+/// Writes the decrypted lines to 'decrypt.txt' and the key to 'key-new.txt',
+/// with `_` for the unknown bytes.
+fn save_results(lines: &[Vec<u8>], key: &[Option<u8>]) -> Result<(), Box<dyn Error>> {
+    let mut decrypt_file = File::create("decrypt.txt")?;
+    for line in attack::decrypt(lines, key) {
+        decrypt_file.write_all(&line)?;
+        writeln!(decrypt_file)?;
+    }
+
+    let key: Vec<u8> = key.iter().map(|k| k.unwrap_or(attack::UNKNOWN)).collect();
+    File::create("key-new.txt")?.write_all(&key)?;
+
     Ok(())
 }
 
+/// Crib dragging on 'crypto.txt', starting from the key in 'key-new.txt'
+/// when the cryptanalysis has already been run.
+fn interactive(chunk_size: usize, format: Format) -> Result<(), Box<dyn Error>> {
+    let ciphertext = read_ciphertext(format)?;
+    let lines = attack::split_lines(&ciphertext, chunk_size);
+
+    let key = match fs::read("key-new.txt") {
+        Ok(key) if key.len() == chunk_size => key
+            .into_iter()
+            .map(|k| (k != attack::UNKNOWN).then_some(k))
+            .collect(),
+        _ => vec![None; chunk_size],
+    };
+
+    crib::run(lines, key, save_results)
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
-    let mode = if args.prepare {
+    let mode = if args.generate {
+        Mode::Generate
+    } else if args.prepare {
         Mode::Prepare
+    } else if args.encrypt && args.repeating {
+        Mode::RepeatingEncrypt
     } else if args.encrypt {
         Mode::Encrypt
+    } else if args.cryptanalysis && args.repeating {
+        Mode::RepeatingCryptanalysis
+    } else if args.interactive {
+        Mode::Interactive
     } else {
         Mode::Cryptanalysis
     };
 
-    let key = fs::read_to_string("key.txt")
-        .map_err(|_| "Error: File 'key.txt' not found.")?
-        .trim()
-        .to_string();
-
-    let chunk_size = key.len();
+    let language = Language::load(&args.language)?;
     let config = Config(mode);
 
-    match config.0 {
-        Mode::Prepare => {
-            if let Err(e) = prepare_text(chunk_size) {
-                println!("{}", e);
-            }
+    let result = match config.0 {
+        Mode::Generate => match args.length {
+            Some(length) if length > 0 => generate_key(length, args.key_alphabet, args.key_format),
+            _ => Err("Error: Give a positive key length with --length.".into()),
+        },
+        Mode::Prepare => key_length(&args).and_then(prepare_text),
+        Mode::Encrypt => read_key(&args).and_then(|key| encrypt(&key, args.format)),
+        Mode::Cryptanalysis => {
+            key_length(&args).and_then(|length| cryptanalysis(length, &language, args.format))
         }
-        Mode::Encrypt => {
-            if let Err(e) = encrypt() {
-                println!("{}", e);
-            }
+        Mode::RepeatingEncrypt => {
+            read_key(&args).and_then(|key| repeating_encrypt(&key, args.format))
         }
-        Mode::Cryptanalysis => {
-            println!("implementation in python lol")
+        Mode::RepeatingCryptanalysis => {
+            repeating_cryptanalysis(args.max_key_size, &language, args.format)
         }
+        Mode::Interactive => key_length(&args).and_then(|length| interactive(length, args.format)),
+    };
+    if let Err(e) = result {
+        println!("{}", e);
     }
 
     Ok(())